@@ -0,0 +1,2217 @@
+//! Terminal emulation backed by `alacritty_terminal`
+
+use std::collections::HashMap;
+use std::sync::{Arc, mpsc};
+use std::time::{Duration, Instant};
+
+use alacritty_terminal::Term;
+use alacritty_terminal::event::{Event, EventListener};
+use alacritty_terminal::grid::{Cursor, Dimensions, Scroll};
+use alacritty_terminal::index::{Column, Line, Point};
+use alacritty_terminal::term::cell::{Cell as TermCell, CellExtra, Flags};
+use alacritty_terminal::term::{Config, TermMode};
+use vte::ansi::{self, Handler, NamedColor, Processor, Timeout};
+use vte::{Params, Parser, Perform};
+
+use crate::term::{Cell, CellColor, Color, Grid, Style, UnderlineStyle};
+use crate::theme::Theme;
+
+/// Terminal dimensions as understood by `alacritty_terminal`
+struct TermSize {
+    rows: usize,
+    cols: usize,
+}
+
+impl Dimensions for TermSize {
+    fn total_lines(&self) -> usize {
+        self.rows
+    }
+
+    fn screen_lines(&self) -> usize {
+        self.rows
+    }
+
+    fn columns(&self) -> usize {
+        self.cols
+    }
+}
+
+/// Default number of lines kept in the scrollback history
+pub const DEFAULT_SCROLLBACK_LINES: usize = 10_000;
+
+/// Maximum number of entries kept on each title stack
+const TITLE_STACK_DEPTH: usize = 4096;
+
+/// Longest a synchronized update may hold back output
+const SYNC_TIMEOUT: Duration = Duration::from_millis(150);
+
+/// Most output a synchronized update may hold back
+const SYNC_BUFFER_LIMIT: usize = 2 * 1024 * 1024;
+
+/// Private modes whose DECRQM reports come from the emulator
+///
+/// `alacritty_terminal` reports these as unsupported.
+const REPORTED_MODES: [u16; 3] = [47, 1047, 2031];
+
+/// Sequences the emulator handles on top of `alacritty_terminal`
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Sequence {
+    /// ED 1 - erase from the start of the screen to the cursor
+    EraseAbove,
+    /// DECSET/DECRST 47 and 1047 - switch to or from the alternate screen
+    AltScreen {
+        enter: bool,
+        /// 1047 clears the alternate screen when leaving it
+        clear: bool,
+    },
+    /// DECSET 1048 - save the cursor
+    SaveCursor,
+    /// DECRST 1048 - restore the cursor
+    RestoreCursor,
+    /// BSU (DECSET 2026) - begin a synchronized update
+    BeginSync,
+    /// ESU (DECRST 2026) - end a synchronized update
+    EndSync,
+    /// SGR changes `alacritty_terminal` does not track
+    Attributes(Attributes),
+    /// OSC 8 - start or end a hyperlink, replacing the cursor's extra cell data
+    Hyperlink,
+    /// DSR 996 - report the color scheme
+    ColorSchemeQuery,
+    /// DECSET/DECRST 2031 - enable or disable color scheme notifications
+    ColorSchemeUpdates(bool),
+    /// DECRQM for a private mode handled here rather than by `alacritty_terminal`
+    ModeQuery(u16),
+    /// XTVERSION - report the terminal's name and version
+    VersionQuery,
+    /// OSC 0/1/2 - set the icon name and/or window title
+    SetTitle(TitleParts, String),
+    /// XTWINOPS 22 - save the icon name and/or window title
+    PushTitle(TitleParts),
+    /// XTWINOPS 23 - restore the icon name and/or window title
+    PopTitle(TitleParts),
+    /// OSC 17/19 - query the highlight background or foreground
+    HighlightQuery {
+        /// OSC 19 rather than 17
+        foreground: bool,
+        /// Terminated by BEL rather than ST
+        bell: bool,
+    },
+}
+
+/// Which of the icon name and window title a sequence affects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TitleParts {
+    icon_name: bool,
+    title: bool,
+}
+
+impl TitleParts {
+    /// Decode the selector shared by OSC 0/1/2 and XTWINOPS 22/23
+    fn new(selector: u16) -> Option<Self> {
+        match selector {
+            0 => Some(Self {
+                icon_name: true,
+                title: true,
+            }),
+            1 => Some(Self {
+                icon_name: true,
+                title: false,
+            }),
+            2 => Some(Self {
+                icon_name: false,
+                title: true,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// SGR blink (5, 6, 25), overline (53, 55) and double underline (21)
+///
+/// Underline color changes (58, 59) and resets are noted too, since they
+/// replace the cursor's extra cell data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Attributes {
+    blink: Option<bool>,
+    overline: Option<bool>,
+    /// SGR 21 was the last underline change
+    double_underline: bool,
+    /// SGR 21 was seen, which `vte` reads as "bold off"
+    keep_bold: bool,
+    /// Last explicit bold change
+    bold: Option<bool>,
+}
+
+/// Attributes `alacritty_terminal` has no flags for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Marks {
+    blink: bool,
+    overline: bool,
+}
+
+/// Cursor attributes from before an intercepted sequence
+#[derive(Debug, Clone, Copy)]
+struct Previous {
+    flags: Flags,
+    marks: Marks,
+}
+
+/// Recognizes [`Sequence`]s in the output stream
+///
+/// Runs a second `vte` parser alongside the one driving the terminal so
+/// that intercepted sequences can be applied in stream order.
+#[derive(Default)]
+struct Interceptor {
+    pending: Vec<Sequence>,
+}
+
+impl Perform for Interceptor {
+    fn terminated(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {
+        if ignore {
+            return;
+        }
+        let first = params.iter().next().map_or(0, |param| param[0]);
+        match (action, intermediates) {
+            ('J', []) if first == 1 => self.pending.push(Sequence::EraseAbove),
+            ('h' | 'l', [b'?']) => {
+                let set = action == 'h';
+                for param in params {
+                    match param[0] {
+                        mode @ (47 | 1047) => self.pending.push(Sequence::AltScreen {
+                            enter: set,
+                            clear: mode == 1047,
+                        }),
+                        1048 if set => self.pending.push(Sequence::SaveCursor),
+                        1048 => self.pending.push(Sequence::RestoreCursor),
+                        2026 if set => self.pending.push(Sequence::BeginSync),
+                        2026 => self.pending.push(Sequence::EndSync),
+                        2031 => self.pending.push(Sequence::ColorSchemeUpdates(set)),
+                        _ => {}
+                    }
+                }
+            }
+            ('n', [b'?']) if first == 996 => self.pending.push(Sequence::ColorSchemeQuery),
+            ('p', [b'?', b'$']) if REPORTED_MODES.contains(&first) => {
+                self.pending.push(Sequence::ModeQuery(first));
+            }
+            ('q', [b'>']) if first == 0 => self.pending.push(Sequence::VersionQuery),
+            ('t', []) if matches!(first, 22 | 23) => {
+                let selector = params.iter().nth(1).map_or(0, |param| param[0]);
+                if let Some(parts) = TitleParts::new(selector) {
+                    self.pending.push(if first == 22 {
+                        Sequence::PushTitle(parts)
+                    } else {
+                        Sequence::PopTitle(parts)
+                    });
+                }
+            }
+            ('m', []) => {
+                if let Some(sequence) = sgr_attributes(params) {
+                    self.pending.push(sequence);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+        let sequence = match params {
+            [b"8", ..] => Sequence::Hyperlink,
+            [b"17" | b"19", b"?"] => Sequence::HighlightQuery {
+                foreground: params[0] == b"19",
+                bell: bell_terminated,
+            },
+            [[code @ b'0'..=b'2'], text @ ..] => {
+                let Some(parts) = TitleParts::new(u16::from(code - b'0')) else {
+                    return;
+                };
+                // The title may itself contain semicolons
+                let text: Vec<_> = text
+                    .iter()
+                    .map(|part| String::from_utf8_lossy(part))
+                    .collect();
+                Sequence::SetTitle(parts, text.join(";"))
+            }
+            _ => return,
+        };
+        self.pending.push(sequence);
+    }
+}
+
+/// Output held back by a synchronized update
+struct SyncUpdate {
+    buffer: Vec<u8>,
+    deadline: Instant,
+}
+
+/// Passes `alacritty_terminal` events back to the emulator
+struct EventProxy(mpsc::Sender<Event>);
+
+impl EventListener for EventProxy {
+    fn send_event(&self, event: Event) {
+        let _ = self.0.send(event);
+    }
+}
+
+/// Collect the SGR attributes `alacritty_terminal` does not track
+fn sgr_attributes(params: &Params) -> Option<Sequence> {
+    if params.is_empty() {
+        return Some(Sequence::Attributes(Attributes {
+            blink: Some(false),
+            overline: Some(false),
+            ..Attributes::default()
+        }));
+    }
+
+    let mut attrs = Attributes::default();
+    let mut touched = false;
+    let mut iter = params.iter();
+    while let Some(param) = iter.next() {
+        match param {
+            [0] => {
+                attrs = Attributes {
+                    blink: Some(false),
+                    overline: Some(false),
+                    bold: Some(false),
+                    keep_bold: attrs.keep_bold,
+                    double_underline: false,
+                };
+                touched = true;
+            }
+            [1] => attrs.bold = Some(true),
+            [22] => attrs.bold = Some(false),
+            [4 | 24] | [4, _] => attrs.double_underline = false,
+            [5 | 6] => {
+                attrs.blink = Some(true);
+                touched = true;
+            }
+            [21] => {
+                attrs.double_underline = true;
+                attrs.keep_bold = true;
+                touched = true;
+            }
+            [25] => {
+                attrs.blink = Some(false);
+                touched = true;
+            }
+            [53] => {
+                attrs.overline = Some(true);
+                touched = true;
+            }
+            [55] => {
+                attrs.overline = Some(false);
+                touched = true;
+            }
+            [58 | 59] | [58, ..] => {
+                touched = true;
+                // Skip the arguments of the semicolon-separated form
+                if param == [58] {
+                    skip_color_arguments(&mut iter);
+                }
+            }
+            [38 | 48] => skip_color_arguments(&mut iter),
+            _ => {}
+        }
+    }
+    touched.then_some(Sequence::Attributes(attrs))
+}
+
+/// Skip the arguments of a semicolon-separated extended color
+fn skip_color_arguments<'a>(iter: &mut impl Iterator<Item = &'a [u16]>) {
+    match iter.next() {
+        Some([5]) => {
+            iter.next();
+        }
+        Some([2]) => {
+            iter.nth(2);
+        }
+        _ => {}
+    }
+}
+
+/// Terminal emulator fed with raw PTY output
+///
+/// Bytes are parsed by the `vte` processor and applied to an
+/// `alacritty_terminal::Term`; the visible screen can then be read back
+/// as a [`Grid`].
+pub struct Emulator {
+    term: Term<EventProxy>,
+    events: mpsc::Receiver<Event>,
+    scrollback_lines: usize,
+    parser: Processor,
+    scanner: Parser,
+    interceptor: Interceptor,
+    /// Blink and overline of cells, by the address of their extra data
+    ///
+    /// `alacritty_terminal` cells have no flags for these, but they do carry
+    /// their extra data (`Arc<CellExtra>`) wherever the cell goes: scrolling,
+    /// scrollback, reflow, saved cursors. Cells printed from the cursor
+    /// template share its extra data, so while blink or overline is on the
+    /// template gets extra data of its own, kept alive here so the address
+    /// stays unique. A combining character gives the cell it joins a new
+    /// copy of the data, which [`Emulator::advance_marked`] marks again.
+    marked: HashMap<usize, (Arc<CellExtra>, Marks)>,
+    /// Output held back until the synchronized update (DECSET 2026) ends
+    sync: Option<SyncUpdate>,
+    /// Primary screen cursor saved with DECSC, kept across 47/1047
+    saved_cursor: Option<Cursor<TermCell>>,
+    /// Replies for the program, such as answers to status queries
+    responses: Vec<u8>,
+    /// Whether the host uses a dark color scheme
+    dark_mode: bool,
+    /// Whether the program asked to hear about color scheme changes
+    color_scheme_updates: bool,
+    /// Colors reported to programs that query them
+    theme: Theme,
+    title: String,
+    icon_name: String,
+    title_stack: Vec<String>,
+    icon_name_stack: Vec<String>,
+}
+
+impl std::fmt::Debug for Emulator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Emulator")
+            .field("rows", &self.rows())
+            .field("cols", &self.cols())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Emulator {
+    /// Create a new emulator with the given dimensions
+    ///
+    /// # Panics
+    ///
+    /// Panics if rows or cols is 0.
+    #[must_use]
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self::with_scrollback(rows, cols, DEFAULT_SCROLLBACK_LINES)
+    }
+
+    /// Create a new emulator keeping up to `scrollback_lines` lines of history
+    ///
+    /// # Panics
+    ///
+    /// Panics if rows or cols is 0.
+    #[must_use]
+    pub fn with_scrollback(rows: usize, cols: usize, scrollback_lines: usize) -> Self {
+        assert!(rows > 0, "rows must be positive");
+        assert!(cols > 0, "cols must be positive");
+
+        let size = TermSize { rows, cols };
+        let (sender, events) = mpsc::channel();
+        Self {
+            term: Term::new(
+                scrollback_config(scrollback_lines),
+                &size,
+                EventProxy(sender),
+            ),
+            events,
+            scrollback_lines,
+            parser: Processor::new(),
+            scanner: Parser::new(),
+            interceptor: Interceptor::default(),
+            marked: HashMap::new(),
+            sync: None,
+            saved_cursor: None,
+            responses: Vec::new(),
+            dark_mode: true,
+            color_scheme_updates: false,
+            theme: Theme::default(),
+            title: String::new(),
+            icon_name: String::new(),
+            title_stack: Vec::new(),
+            icon_name_stack: Vec::new(),
+        }
+    }
+
+    /// Get the number of rows
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        self.term.screen_lines()
+    }
+
+    /// Get the number of columns
+    #[must_use]
+    pub fn cols(&self) -> usize {
+        self.term.columns()
+    }
+
+    /// Resize the screen, reflowing its contents
+    ///
+    /// Soft-wrapped lines are rejoined and rewrapped across the screen and
+    /// scrollback, and the cursor stays on the same character of its line.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rows` or `cols` is zero.
+    pub fn resize(&mut self, rows: usize, cols: usize) {
+        assert!(rows > 0, "rows must be positive");
+        assert!(cols > 0, "cols must be positive");
+
+        self.term.resize(TermSize { rows, cols });
+    }
+
+    /// Get the maximum number of lines kept in the scrollback history
+    #[must_use]
+    pub const fn scrollback_lines(&self) -> usize {
+        self.scrollback_lines
+    }
+
+    /// Change the scrollback limit, dropping the oldest lines if it shrinks
+    pub fn set_scrollback_lines(&mut self, scrollback_lines: usize) {
+        self.scrollback_lines = scrollback_lines;
+        self.term.set_options(scrollback_config(scrollback_lines));
+    }
+
+    /// Get the number of lines currently in the scrollback history
+    #[must_use]
+    pub fn history_len(&self) -> usize {
+        self.term.grid().history_size()
+    }
+
+    /// Get a row from the scrollback history
+    ///
+    /// Offset 0 is the line most recently scrolled off the top of the
+    /// screen. Returns `None` if the offset is beyond the stored history.
+    #[must_use]
+    pub fn history_row(&self, offset: usize) -> Option<Vec<Cell>> {
+        if offset >= self.history_len() {
+            return None;
+        }
+        let line = i32::try_from(offset).ok()?;
+        Some(self.convert_row(Line(-1 - line)))
+    }
+
+    /// Get how many lines the view is scrolled back into history
+    #[must_use]
+    pub fn display_offset(&self) -> usize {
+        self.term.grid().display_offset()
+    }
+
+    /// Scroll the view by `lines`, positive values moving back into history
+    pub fn scroll_lines(&mut self, lines: i32) {
+        self.term.scroll_display(Scroll::Delta(lines));
+    }
+
+    /// Scroll the view back by one screen
+    pub fn scroll_page_up(&mut self) {
+        self.term.scroll_display(Scroll::PageUp);
+    }
+
+    /// Scroll the view forward by one screen
+    pub fn scroll_page_down(&mut self) {
+        self.term.scroll_display(Scroll::PageDown);
+    }
+
+    /// Scroll the view to the oldest line in history
+    pub fn scroll_to_top(&mut self) {
+        self.term.scroll_display(Scroll::Top);
+    }
+
+    /// Scroll the view back to the live screen
+    pub fn scroll_to_bottom(&mut self) {
+        self.term.scroll_display(Scroll::Bottom);
+    }
+
+    /// Handle mouse wheel movement of `lines` at cell `(row, col)`
+    ///
+    /// Positive values scroll back into history. When the program handles
+    /// scrolling itself (mouse reporting, or the alternate screen), the view
+    /// is left alone and the bytes to send to the program are returned.
+    pub fn wheel(&mut self, lines: i32, row: usize, col: usize) -> Option<Vec<u8>> {
+        let mode = *self.term.mode();
+        let count = usize::try_from(lines.unsigned_abs()).unwrap_or(0);
+        if count == 0 {
+            return None;
+        }
+
+        if mode.intersects(TermMode::MOUSE_MODE) {
+            let button = if lines > 0 { 64 } else { 65 };
+            return Some(mouse_report(mode, button, row, col).repeat(count));
+        }
+
+        if mode.contains(TermMode::ALT_SCREEN) {
+            if !mode.contains(TermMode::ALTERNATE_SCROLL) {
+                return None;
+            }
+            let key: &[u8] = match (lines > 0, mode.contains(TermMode::APP_CURSOR)) {
+                (true, true) => b"\x1bOA",
+                (true, false) => b"\x1b[A",
+                (false, true) => b"\x1bOB",
+                (false, false) => b"\x1b[B",
+            };
+            return Some(key.repeat(count));
+        }
+
+        self.scroll_lines(lines);
+        None
+    }
+
+    /// Feed output from the PTY into the terminal
+    ///
+    /// Output inside a synchronized update (DECSET 2026) is held back
+    /// until the update ends or times out.
+    pub fn advance(&mut self, bytes: &[u8]) {
+        self.flush_expired_sync();
+
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            let fed = if self.sync.is_some() {
+                self.advance_sync(rest)
+            } else {
+                self.advance_parsers(rest, true)
+            };
+            rest = &rest[fed..];
+        }
+    }
+
+    /// Hold back output during a synchronized update, ending it at the ESU
+    ///
+    /// Returns how much output was taken.
+    fn advance_sync(&mut self, bytes: &[u8]) -> usize {
+        let scanned = self
+            .scanner
+            .advance_until_terminated(&mut self.interceptor, bytes);
+        let mut end = false;
+        // Other sequences are seen again when the update is applied
+        for sequence in std::mem::take(&mut self.interceptor.pending) {
+            match sequence {
+                Sequence::BeginSync => self.begin_sync(),
+                Sequence::EndSync => end = true,
+                _ => {}
+            }
+        }
+
+        if let Some(sync) = &mut self.sync {
+            sync.buffer.extend_from_slice(&bytes[..scanned]);
+            end |= sync.buffer.len() > SYNC_BUFFER_LIMIT;
+        }
+        if end {
+            self.end_sync();
+        }
+        scanned
+    }
+
+    /// Start a synchronized update, or extend the one in progress
+    fn begin_sync(&mut self) {
+        let deadline = Instant::now() + SYNC_TIMEOUT;
+        match &mut self.sync {
+            Some(sync) => sync.deadline = deadline,
+            None => {
+                self.sync = Some(SyncUpdate {
+                    buffer: Vec::new(),
+                    deadline,
+                });
+            }
+        }
+    }
+
+    /// Apply the output held back by a synchronized update
+    fn end_sync(&mut self) {
+        if let Some(sync) = self.sync.take() {
+            // The update began with the scanner in its ground state
+            self.scanner = Parser::new();
+            self.advance_parsers(&sync.buffer, false);
+        }
+    }
+
+    /// Feed output to the terminal and the interceptor
+    ///
+    /// With `sync` set, stops after a BSU so the rest can be held back.
+    /// Returns how much output was fed.
+    fn advance_parsers(&mut self, bytes: &[u8], sync: bool) -> usize {
+        // Hand output to the terminal in runs, stopping after each
+        // intercepted sequence so it is applied in order
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            let scanned = self
+                .scanner
+                .advance_until_terminated(&mut self.interceptor, rest);
+            let (run, tail) = rest.split_at(scanned);
+            rest = tail;
+            if self.interceptor.pending.is_empty() {
+                self.advance_term(run);
+                break;
+            }
+
+            // Note the attributes in effect before the final byte
+            let (run, last) = run.split_at(run.len() - 1);
+            self.advance_term(run);
+            self.process_events();
+            let template = &self.term.grid().cursor.template;
+            let previous = Previous {
+                flags: template.flags,
+                marks: self.marks(template),
+            };
+            self.advance_term(last);
+
+            self.process_events();
+            let mut begins_sync = false;
+            for sequence in std::mem::take(&mut self.interceptor.pending) {
+                begins_sync |= sequence == Sequence::BeginSync;
+                self.apply(sequence, previous);
+            }
+            if sync && begins_sync {
+                self.begin_sync();
+                break;
+            }
+        }
+        self.process_events();
+        bytes.len() - rest.len()
+    }
+
+    /// Feed output to `alacritty_terminal`
+    fn advance_term(&mut self, bytes: &[u8]) {
+        // Combining characters can join marked cells whatever the cursor's marks
+        if bytes.is_ascii() || self.marked.is_empty() {
+            self.parser.advance(&mut self.term, bytes);
+        } else {
+            self.advance_marked(bytes);
+        }
+        // Synchronized updates are handled by the emulator, but
+        // `vte` also starts one for 2026 among other modes; end it at once
+        if self.parser.sync_timeout().pending_timeout() {
+            self.parser.stop_sync(&mut self.term);
+        }
+    }
+
+    /// Feed output while some cells have blink or overline
+    ///
+    /// A combining character copies the extra data of the cell it joins,
+    /// so each non-ASCII character is fed on its own to carry the cell's
+    /// marks over.
+    fn advance_marked(&mut self, bytes: &[u8]) {
+        let mut rest = bytes;
+        while let Some(start) = rest.iter().position(|&byte| byte >= 0xC0) {
+            let len = rest[start + 1..]
+                .iter()
+                .take_while(|&&byte| byte & 0xC0 == 0x80)
+                .count();
+            let (run, tail) = rest.split_at(start + 1 + len);
+            let (run, c) = run.split_at(start);
+            self.parser.advance(&mut self.term, run);
+
+            let (cursor, point) = self.combining_target();
+            let cell = &self.term.grid()[point];
+            let marks = self.marks(cell);
+            let zerowidth = cell.zerowidth().map_or(0, <[_]>::len);
+
+            self.parser.advance(&mut self.term, c);
+
+            // The character joined the cell if it added a zero-width one
+            let cell = &self.term.grid()[point];
+            let joined = self.term.grid().cursor.point == cursor
+                && cell.zerowidth().map_or(0, <[_]>::len) == zerowidth + 1;
+            if joined && self.marks(cell) != marks {
+                if let Some(extra) = cell.extra.clone() {
+                    self.mark(extra, marks);
+                }
+            }
+            rest = tail;
+        }
+        self.parser.advance(&mut self.term, rest);
+    }
+
+    /// Get the cursor and the cell a combining character would join
+    fn combining_target(&self) -> (Point, Point) {
+        let grid = self.term.grid();
+        let cursor = grid.cursor.point;
+        let mut point = cursor;
+        if !grid.cursor.input_needs_wrap {
+            point.column.0 = point.column.saturating_sub(1);
+        }
+        if grid[point].flags.contains(Flags::WIDE_CHAR_SPACER) {
+            point.column.0 = point.column.saturating_sub(1);
+        }
+        (cursor, point)
+    }
+
+    /// Take the bytes queued for the program, such as answers to queries
+    ///
+    /// These must be written to the PTY to reach the program.
+    pub fn take_responses(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.responses)
+    }
+
+    /// Check whether the host uses a dark color scheme
+    #[must_use]
+    pub fn dark_mode(&self) -> bool {
+        self.dark_mode
+    }
+
+    /// Set whether the host uses a dark color scheme (default: dark)
+    ///
+    /// Programs ask for it with `CSI ? 996 n`. Those that enabled color
+    /// scheme notifications (DECSET 2031) get a report queued when it
+    /// changes.
+    pub fn set_dark_mode(&mut self, dark: bool) {
+        if dark != self.dark_mode {
+            self.dark_mode = dark;
+            if self.color_scheme_updates {
+                self.report_color_scheme();
+            }
+        }
+    }
+
+    /// Get the window title set by the program (OSC 0/2), empty if none
+    #[must_use]
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Get the icon name set by the program (OSC 0/1), empty if none
+    #[must_use]
+    pub fn icon_name(&self) -> &str {
+        &self.icon_name
+    }
+
+    /// Get the theme reported to programs that query colors
+    #[must_use]
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Set the theme reported to programs that query colors
+    ///
+    /// Answers OSC 4, 10, 11, 12, 17 and 19 queries for colors the program
+    /// has not overridden, and sets the dark mode from the background.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.set_dark_mode(theme.is_dark());
+    }
+
+    /// Queue the replies `alacritty_terminal` produced, answering color queries
+    fn process_events(&mut self) {
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                // The emulator answers for these modes itself
+                Event::PtyWrite(reply) if is_reported_mode(&reply) => {}
+                Event::PtyWrite(reply) => self.reply(&reply),
+                Event::ColorRequest(index, format) => {
+                    let Color { r, g, b } = self.query_color(index);
+                    self.reply(&format(ansi::Rgb { r, g, b }));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Queue bytes for the program
+    fn reply(&mut self, reply: &str) {
+        self.responses.extend_from_slice(reply.as_bytes());
+    }
+
+    /// Current value of a color table entry: the program's override or the theme's
+    fn query_color(&self, index: usize) -> Color {
+        const FOREGROUND: usize = NamedColor::Foreground as usize;
+        const BACKGROUND: usize = NamedColor::Background as usize;
+        const CURSOR: usize = NamedColor::Cursor as usize;
+
+        if let Some(rgb) = self.term.colors()[index] {
+            return rgb.into();
+        }
+        let theme = &self.theme;
+        match index {
+            FOREGROUND => theme.foreground,
+            BACKGROUND => theme.background,
+            CURSOR => theme.cursor.unwrap_or(theme.foreground),
+            _ => u8::try_from(index).map_or(theme.foreground, |index| {
+                theme.resolve(CellColor::Indexed(index), theme.foreground)
+            }),
+        }
+    }
+
+    /// Queue a DSR 997 color scheme report: 1 for dark, 2 for light
+    fn report_color_scheme(&mut self) {
+        let scheme = if self.dark_mode { 1 } else { 2 };
+        self.reply(&format!("\x1b[?997;{scheme}n"));
+    }
+
+    /// Apply an intercepted sequence after `alacritty_terminal` has seen it
+    ///
+    /// `previous` holds the cursor attributes from before the sequence.
+    fn apply(&mut self, sequence: Sequence, previous: Previous) {
+        match sequence {
+            Sequence::EraseAbove => {
+                // `alacritty_terminal` skips the full-line clear when the
+                // cursor is on the second line, leaving the first intact
+                let grid = self.term.grid_mut();
+                if grid.cursor.point.line == Line(1) {
+                    grid.reset_region(..Line(1));
+                }
+            }
+            Sequence::AltScreen { enter, clear } => self.switch_screen(enter, clear),
+            Sequence::SaveCursor => self.term.save_cursor_position(),
+            Sequence::RestoreCursor => self.term.restore_cursor_position(),
+            Sequence::ColorSchemeQuery => self.report_color_scheme(),
+            Sequence::ColorSchemeUpdates(enabled) => self.color_scheme_updates = enabled,
+            Sequence::ModeQuery(mode) => {
+                let set = match mode {
+                    2031 => self.color_scheme_updates,
+                    _ => self.is_alt_screen(),
+                };
+                // 1 = set, 2 = reset
+                let state = if set { 1 } else { 2 };
+                self.reply(&format!("\x1b[?{mode};{state}$y"));
+            }
+            Sequence::SetTitle(parts, text) => {
+                if parts.icon_name {
+                    self.icon_name.clone_from(&text);
+                }
+                if parts.title {
+                    self.title = text;
+                }
+            }
+            Sequence::PushTitle(parts) => {
+                if parts.icon_name {
+                    push_title(&mut self.icon_name_stack, &self.icon_name);
+                }
+                if parts.title {
+                    push_title(&mut self.title_stack, &self.title);
+                }
+            }
+            Sequence::PopTitle(parts) => {
+                let icon_name = parts.icon_name.then(|| self.icon_name_stack.pop());
+                if let Some(icon_name) = icon_name.flatten() {
+                    self.icon_name = icon_name;
+                }
+                let title = parts.title.then(|| self.title_stack.pop());
+                if let Some(title) = title.flatten() {
+                    self.title = title;
+                }
+            }
+            Sequence::VersionQuery => {
+                self.reply(concat!(
+                    "\x1bP>|dioxus-terminal(",
+                    env!("CARGO_PKG_VERSION"),
+                    ")\x1b\\"
+                ));
+            }
+            Sequence::HighlightQuery { foreground, bell } => {
+                // Without a selection color, highlighting reverses the text
+                let theme = &self.theme;
+                let (code, color) = match (foreground, theme.selection) {
+                    (false, selection) => (17, selection.unwrap_or(theme.foreground)),
+                    (true, Some(_)) => (19, theme.foreground),
+                    (true, None) => (19, theme.background),
+                };
+                let Color { r, g, b } = color;
+                let terminator = if bell { "\x07" } else { "\x1b\\" };
+                self.reply(&format!(
+                    "\x1b]{code};rgb:{r:02x}{r:02x}/{g:02x}{g:02x}/{b:02x}{b:02x}{terminator}"
+                ));
+            }
+            Sequence::Attributes(attrs) => self.apply_attributes(attrs, previous),
+            // Synchronized updates are started as the output is fed
+            Sequence::BeginSync | Sequence::EndSync => {}
+            Sequence::Hyperlink => self.set_marks(previous.marks),
+        }
+    }
+
+    /// Switch to or from the alternate screen for DECSET/DECRST 47 and 1047
+    ///
+    /// Unlike 1049, these leave the cursor saved with DECSC alone.
+    fn switch_screen(&mut self, enter: bool, clear: bool) {
+        if enter == self.is_alt_screen() {
+            return;
+        }
+        if enter {
+            // `alacritty_terminal` saves the cursor on the way in
+            self.saved_cursor = Some(self.term.grid().saved_cursor.clone());
+            self.term.swap_alt();
+        } else {
+            if clear {
+                self.term.grid_mut().reset_region(..);
+            }
+            self.term.swap_alt();
+            if let Some(cursor) = self.saved_cursor.take() {
+                self.term.grid_mut().saved_cursor = cursor;
+            }
+        }
+    }
+
+    /// Apply SGR attributes on top of those `alacritty_terminal` set
+    fn apply_attributes(&mut self, attrs: Attributes, previous: Previous) {
+        let template = &mut self.term.grid_mut().cursor.template;
+        if attrs.keep_bold {
+            let bold = attrs.bold.unwrap_or(previous.flags.contains(Flags::BOLD));
+            template.flags.set(Flags::BOLD, bold);
+        }
+        if attrs.double_underline {
+            template.flags.remove(Flags::ALL_UNDERLINES);
+            template.flags.insert(Flags::DOUBLE_UNDERLINE);
+        }
+
+        self.set_marks(Marks {
+            blink: attrs.blink.unwrap_or(previous.marks.blink),
+            overline: attrs.overline.unwrap_or(previous.marks.overline),
+        });
+    }
+
+    /// Get the blink and overline of a cell
+    fn marks(&self, cell: &TermCell) -> Marks {
+        cell.extra
+            .as_ref()
+            .and_then(|extra| self.marked.get(&Arc::as_ptr(extra).addr()))
+            .map_or_else(Marks::default, |&(_, marks)| marks)
+    }
+
+    /// Set the blink and overline of text printed from now on
+    fn set_marks(&mut self, marks: Marks) {
+        let template = &self.term.grid().cursor.template;
+        if self.marks(template) == marks {
+            return;
+        }
+
+        let extra = template.extra.as_deref().cloned().unwrap_or_default();
+        let extra = if marks == Marks::default() {
+            (extra != CellExtra::default()).then(|| Arc::new(extra))
+        } else {
+            let extra = Arc::new(extra);
+            self.mark(extra.clone(), marks);
+            Some(extra)
+        };
+        self.term.grid_mut().cursor.template.extra = extra;
+    }
+
+    /// Remember the blink and overline of cells with the given extra data
+    fn mark(&mut self, extra: Arc<CellExtra>, marks: Marks) {
+        // Forget the data no cell uses any more before the map grows
+        if self.marked.len() == self.marked.capacity() {
+            self.marked
+                .retain(|_, (extra, _)| Arc::strong_count(extra) > 1);
+        }
+        self.marked
+            .insert(Arc::as_ptr(&extra).addr(), (extra, marks));
+    }
+
+    /// Apply a synchronized update (DECSET 2026) whose timeout has expired
+    ///
+    /// Returns `true` if buffered output was applied to the screen.
+    pub fn flush_expired_sync(&mut self) -> bool {
+        let expired = self
+            .sync
+            .as_ref()
+            .is_some_and(|sync| sync.deadline <= Instant::now());
+        if expired {
+            self.end_sync();
+        }
+        expired
+    }
+
+    /// Get the cursor position as `(row, col)`
+    #[must_use]
+    pub fn cursor(&self) -> (usize, usize) {
+        let point = self.term.renderable_content().cursor.point;
+        let row = usize::try_from(point.line.0).unwrap_or(0);
+        (row, point.column.0)
+    }
+
+    /// Check whether the program wants the cursor shown (DECTCEM)
+    #[must_use]
+    pub fn cursor_visible(&self) -> bool {
+        self.term.mode().contains(TermMode::SHOW_CURSOR)
+    }
+
+    /// Check whether the alternate screen buffer is active
+    #[must_use]
+    pub fn is_alt_screen(&self) -> bool {
+        self.term.mode().contains(TermMode::ALT_SCREEN)
+    }
+
+    /// Get the cursor position within the view, if it is shown
+    ///
+    /// Returns `None` when the cursor is hidden or scrolled out of view.
+    #[must_use]
+    pub fn viewport_cursor(&self) -> Option<(usize, usize)> {
+        if !self.cursor_visible() {
+            return None;
+        }
+        let (row, col) = self.cursor();
+        let row = row + self.display_offset();
+        (row < self.rows()).then_some((row, col))
+    }
+
+    /// Snapshot the view into a [`Grid`]
+    ///
+    /// This is the live screen unless the view is scrolled back into history.
+    #[must_use]
+    pub fn grid(&self) -> Grid {
+        let rows = self.rows();
+        let mut grid = Grid::new(rows, self.cols());
+
+        let offset = i32::try_from(self.display_offset()).unwrap_or(0);
+        for (row, line) in (0..rows).zip(-offset..) {
+            for (col, cell) in self.convert_row(Line(line)).into_iter().enumerate() {
+                grid.set(row, col, cell);
+            }
+        }
+
+        grid
+    }
+
+    /// Convert a line of the `alacritty_terminal` grid into cells
+    fn convert_row(&self, line: Line) -> Vec<Cell> {
+        let row = &self.term.grid()[line];
+        (0..self.cols())
+            .map(|col| self.convert_cell(&row[Column(col)]))
+            .collect()
+    }
+
+    /// Convert an `alacritty_terminal` cell into a [`Cell`]
+    fn convert_cell(&self, cell: &TermCell) -> Cell {
+        let flags = cell.flags;
+        let marks = self.marks(cell);
+        Cell {
+            c: cell.c,
+            zerowidth: cell.zerowidth().unwrap_or_default().to_vec(),
+            fg: self.convert_color(cell.fg),
+            bg: self.convert_color(cell.bg),
+            style: Style {
+                bold: flags.contains(Flags::BOLD),
+                italic: flags.contains(Flags::ITALIC),
+                underline: flags.intersects(Flags::ALL_UNDERLINES),
+                underline_style: underline_style(flags),
+                strikethrough: flags.contains(Flags::STRIKEOUT),
+                overline: marks.overline,
+                dim: flags.contains(Flags::DIM),
+                inverse: flags.contains(Flags::INVERSE),
+                hidden: flags.contains(Flags::HIDDEN),
+                blink: marks.blink,
+            },
+            width: cell_width(flags),
+            wrapped: flags.contains(Flags::WRAPLINE),
+            underline_color: cell
+                .underline_color()
+                .map(|color| self.convert_color(color)),
+        }
+    }
+
+    /// Convert a terminal color, honoring palette changes made by the program
+    fn convert_color(&self, color: ansi::Color) -> CellColor {
+        match color {
+            ansi::Color::Spec(rgb) => CellColor::Rgb(rgb.into()),
+            ansi::Color::Indexed(index) => self.convert_index(usize::from(index)),
+            ansi::Color::Named(name) => self.convert_index(name as usize),
+        }
+    }
+
+    /// Convert a color table index
+    fn convert_index(&self, index: usize) -> CellColor {
+        if let Some(rgb) = self.term.colors()[index] {
+            return CellColor::Rgb(rgb.into());
+        }
+
+        let dim = NamedColor::DimBlack as usize..=NamedColor::DimWhite as usize;
+        match index {
+            0..=255 => CellColor::Indexed(u8::try_from(index).unwrap_or_default()),
+            // Dimming is drawn from the cell's style
+            i if dim.contains(&i) => {
+                CellColor::Indexed(u8::try_from(i - dim.start()).unwrap_or_default())
+            }
+            _ => CellColor::Default,
+        }
+    }
+}
+
+/// Check whether a reply is a DECRPM report for one of [`REPORTED_MODES`]
+fn is_reported_mode(reply: &str) -> bool {
+    reply
+        .strip_prefix("\x1b[?")
+        .and_then(|reply| reply.split_once(';'))
+        .and_then(|(mode, _)| mode.parse().ok())
+        .is_some_and(|mode| reply.ends_with("$y") && REPORTED_MODES.contains(&mode))
+}
+
+/// Save a title, dropping the oldest entry once the stack is full
+fn push_title(stack: &mut Vec<String>, title: &str) {
+    if stack.len() >= TITLE_STACK_DEPTH {
+        stack.remove(0);
+    }
+    stack.push(title.to_owned());
+}
+
+/// Encode a mouse button press at cell `(row, col)` for the active protocol
+fn mouse_report(mode: TermMode, button: u8, row: usize, col: usize) -> Vec<u8> {
+    if mode.contains(TermMode::SGR_MOUSE) {
+        return format!("\x1b[<{button};{};{}M", col + 1, row + 1).into_bytes();
+    }
+
+    let mut report = b"\x1b[M".to_vec();
+    report.push(32 + button);
+    for pos in [col, row] {
+        let value = u32::try_from(pos).unwrap_or(u32::MAX).saturating_add(33);
+        if mode.contains(TermMode::UTF8_MOUSE) {
+            let c = char::from_u32(value.min(2047)).unwrap_or(' ');
+            report.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+        } else {
+            report.push(u8::try_from(value).unwrap_or(u8::MAX));
+        }
+    }
+    report
+}
+
+/// Terminal configuration with the given scrollback limit
+fn scrollback_config(scrollback_lines: usize) -> Config {
+    Config {
+        scrolling_history: scrollback_lines,
+        ..Config::default()
+    }
+}
+
+/// Number of columns a cell occupies on screen
+fn cell_width(flags: Flags) -> u8 {
+    match (
+        flags.contains(Flags::WIDE_CHAR),
+        flags.contains(Flags::WIDE_CHAR_SPACER),
+    ) {
+        (true, _) => 2,
+        (false, true) => 0,
+        (false, false) => 1,
+    }
+}
+
+/// Shape of the underline set in `flags`
+fn underline_style(flags: Flags) -> UnderlineStyle {
+    if flags.contains(Flags::DOUBLE_UNDERLINE) {
+        UnderlineStyle::Double
+    } else if flags.contains(Flags::UNDERCURL) {
+        UnderlineStyle::Curly
+    } else if flags.contains(Flags::DOTTED_UNDERLINE) {
+        UnderlineStyle::Dotted
+    } else if flags.contains(Flags::DASHED_UNDERLINE) {
+        UnderlineStyle::Dashed
+    } else {
+        UnderlineStyle::Single
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(emulator: &Emulator, row: usize) -> String {
+        let grid = emulator.grid();
+        let line: String = grid
+            .iter_rows()
+            .nth(row)
+            .unwrap()
+            .iter()
+            .map(Cell::text)
+            .collect();
+        line.trim_end().to_string()
+    }
+
+    fn screen(emulator: &Emulator) -> Vec<String> {
+        (0..emulator.rows())
+            .map(|row| text(emulator, row))
+            .collect()
+    }
+
+    #[test]
+    fn test_emulator_new() {
+        let emulator = Emulator::new(24, 80);
+        assert_eq!(emulator.rows(), 24);
+        assert_eq!(emulator.cols(), 80);
+        assert_eq!(emulator.cursor(), (0, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "rows must be positive")]
+    fn test_emulator_zero_rows_panics() {
+        let _ = Emulator::new(0, 80);
+    }
+
+    #[test]
+    fn test_emulator_resize() {
+        let mut emulator = Emulator::new(5, 20);
+        emulator.advance(b"hello\r\nworld");
+        emulator.resize(10, 40);
+        assert_eq!((emulator.rows(), emulator.cols()), (10, 40));
+        assert_eq!(emulator.grid().rows(), 10);
+        assert_eq!(text(&emulator, 1), "world");
+        assert_eq!(emulator.cursor(), (1, 5));
+    }
+
+    #[test]
+    fn test_autowrap_marks_wrapped() {
+        let mut emulator = Emulator::new(3, 5);
+        emulator.advance(b"abcdefg\r\nxy");
+        let grid = emulator.grid();
+        assert!(grid.get(0, 4).unwrap().wrapped);
+        assert!(!grid.get(1, 4).unwrap().wrapped);
+        assert!(!grid.get(2, 4).unwrap().wrapped);
+    }
+
+    #[test]
+    fn test_resize_narrower_rewraps() {
+        let mut emulator = Emulator::new(4, 10);
+        emulator.advance(b"abcdefgh\r\nxy");
+        emulator.resize(4, 4);
+        // Rows gained above the cursor move into the scrollback
+        let first = emulator.history_row(0).unwrap();
+        assert_eq!(row_text(&first), "abcd");
+        assert!(first[3].wrapped);
+        assert_eq!(screen(&emulator), ["efgh", "xy", "", ""]);
+        assert_eq!(emulator.cursor(), (1, 2));
+    }
+
+    #[test]
+    fn test_resize_wider_unwraps() {
+        let mut emulator = Emulator::new(4, 4);
+        emulator.advance(b"abcdefgh\r\nxy");
+        emulator.resize(4, 10);
+        assert_eq!(screen(&emulator), ["abcdefgh", "xy", "", ""]);
+        assert!(!emulator.grid().get(0, 9).unwrap().wrapped);
+        assert_eq!(emulator.cursor(), (1, 2));
+    }
+
+    #[test]
+    fn test_resize_reflows_scrollback() {
+        let mut emulator = Emulator::new(2, 4);
+        emulator.advance(b"abcdefgh\r\nij\r\nkl");
+        assert_eq!(emulator.history_len(), 2);
+        emulator.resize(2, 10);
+        assert_eq!(emulator.history_len(), 1);
+        assert_eq!(row_text(&emulator.history_row(0).unwrap()), "abcdefgh");
+        assert_eq!(screen(&emulator), ["ij", "kl"]);
+    }
+
+    #[test]
+    fn test_resize_keeps_cursor_on_its_character() {
+        let mut emulator = Emulator::new(4, 10);
+        emulator.advance(b"abcdefgh\x1b[1;7H");
+        emulator.resize(4, 4);
+        assert_eq!(emulator.cursor(), (0, 2));
+        emulator.advance(b"X");
+        assert_eq!(row_text(&emulator.history_row(0).unwrap()), "abcd");
+        assert_eq!(screen(&emulator), ["efXh", "", "", ""]);
+    }
+
+    #[test]
+    fn test_advance_plain_text() {
+        let mut emulator = Emulator::new(5, 20);
+        emulator.advance(b"hello\r\nworld");
+        assert_eq!(text(&emulator, 0), "hello");
+        assert_eq!(text(&emulator, 1), "world");
+        assert_eq!(emulator.cursor(), (1, 5));
+    }
+
+    #[test]
+    fn test_advance_scrolls_at_bottom() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"one\r\ntwo\r\nthree");
+        assert_eq!(text(&emulator, 0), "two");
+        assert_eq!(text(&emulator, 1), "three");
+    }
+
+    #[test]
+    fn test_cursor_position() {
+        let mut emulator = Emulator::new(24, 80);
+        emulator.advance(b"\x1b[10;5H");
+        assert_eq!(emulator.cursor(), (9, 4));
+        emulator.advance(b"\x1b[H");
+        assert_eq!(emulator.cursor(), (0, 0));
+        emulator.advance(b"\x1b[3;7f");
+        assert_eq!(emulator.cursor(), (2, 6));
+    }
+
+    #[test]
+    fn test_cursor_relative_movement() {
+        let mut emulator = Emulator::new(24, 80);
+        emulator.advance(b"\x1b[10;10H\x1b[2A");
+        assert_eq!(emulator.cursor(), (7, 9));
+        emulator.advance(b"\x1b[3B");
+        assert_eq!(emulator.cursor(), (10, 9));
+        emulator.advance(b"\x1b[5C");
+        assert_eq!(emulator.cursor(), (10, 14));
+        emulator.advance(b"\x1b[4D");
+        assert_eq!(emulator.cursor(), (10, 10));
+        emulator.advance(b"\x1b[A");
+        assert_eq!(emulator.cursor(), (9, 10));
+    }
+
+    #[test]
+    fn test_cursor_absolute_column_and_row() {
+        let mut emulator = Emulator::new(24, 80);
+        emulator.advance(b"\x1b[5;5H\x1b[20G");
+        assert_eq!(emulator.cursor(), (4, 19));
+        emulator.advance(b"\x1b[12d");
+        assert_eq!(emulator.cursor(), (11, 19));
+    }
+
+    #[test]
+    fn test_cursor_movement_clamped() {
+        let mut emulator = Emulator::new(24, 80);
+        emulator.advance(b"\x1b[100;200H");
+        assert_eq!(emulator.cursor(), (23, 79));
+        emulator.advance(b"\x1b[99A\x1b[99D");
+        assert_eq!(emulator.cursor(), (0, 0));
+    }
+
+    #[test]
+    fn test_cursor_positioned_text() {
+        let mut emulator = Emulator::new(5, 20);
+        emulator.advance(b"prompt\x1b[1;15Hright\x1b[1;7H$ ");
+        assert_eq!(text(&emulator, 0), "prompt$       right");
+    }
+
+    #[test]
+    fn test_cursor_visibility() {
+        let mut emulator = Emulator::new(5, 20);
+        assert!(emulator.cursor_visible());
+        emulator.advance(b"\x1b[?25l");
+        assert!(!emulator.cursor_visible());
+        emulator.advance(b"\x1b[?25h");
+        assert!(emulator.cursor_visible());
+    }
+
+    #[test]
+    fn test_erase_display() {
+        let mut emulator = Emulator::new(3, 10);
+        emulator.advance(b"aaaa\r\nbbbb\r\ncccc\x1b[2;3H\x1b[J");
+        assert_eq!(text(&emulator, 0), "aaaa");
+        assert_eq!(text(&emulator, 1), "bb");
+        assert_eq!(text(&emulator, 2), "");
+
+        emulator.advance(b"\x1b[1;1Haaaa\x1b[2;1Hbbbb\x1b[2;2H\x1b[1J");
+        assert_eq!(text(&emulator, 0), "");
+        assert_eq!(text(&emulator, 1), "  bb");
+
+        emulator.advance(b"\x1b[2J");
+        assert!((0..3).all(|row| text(&emulator, row).is_empty()));
+        assert_eq!(emulator.cursor(), (1, 1));
+    }
+
+    #[test]
+    fn test_erase_line() {
+        let mut emulator = Emulator::new(3, 10);
+        emulator.advance(b"abcdefgh\x1b[1;4H\x1b[K");
+        assert_eq!(text(&emulator, 0), "abc");
+
+        emulator.advance(b"\x1b[1;1Habcdefgh\x1b[1;4H\x1b[1K");
+        assert_eq!(text(&emulator, 0), "    efgh");
+
+        emulator.advance(b"\x1b[2K");
+        assert_eq!(text(&emulator, 0), "");
+    }
+
+    #[test]
+    fn test_erase_characters() {
+        let mut emulator = Emulator::new(3, 10);
+        emulator.advance(b"abcdefgh\x1b[1;3H\x1b[3X");
+        assert_eq!(text(&emulator, 0), "ab   fgh");
+        assert_eq!(emulator.cursor(), (0, 2));
+    }
+
+    #[test]
+    fn test_erase_uses_current_background() {
+        let mut emulator = Emulator::new(3, 10);
+        emulator.advance(b"abc\x1b[44m\x1b[1;2H\x1b[K\x1b[3;1H\x1b[2X");
+        let grid = emulator.grid();
+        let blue = CellColor::Indexed(4);
+        assert_eq!(grid.get(0, 0).unwrap().bg, CellColor::Default);
+        assert!((1..10).all(|col| grid.get(0, col).unwrap().bg == blue));
+        assert_eq!(grid.get(2, 1).unwrap().bg, blue);
+        assert_eq!(grid.get(2, 2).unwrap().bg, CellColor::Default);
+
+        emulator.advance(b"\x1b[2J");
+        let grid = emulator.grid();
+        assert!(grid.iter_rows().flatten().all(|cell| cell.bg == blue));
+    }
+
+    #[test]
+    fn test_alt_screen_restores_primary() {
+        let mut emulator = Emulator::new(5, 20);
+        emulator.advance(b"$ less file\r\n$ ");
+        assert!(!emulator.is_alt_screen());
+
+        emulator.advance(b"\x1b[?1049h\x1b[H\x1b[2Jpager content");
+        assert!(emulator.is_alt_screen());
+        assert_eq!(text(&emulator, 0), "pager content");
+        assert_eq!(text(&emulator, 1), "");
+
+        emulator.advance(b"\x1b[?1049l");
+        assert!(!emulator.is_alt_screen());
+        assert_eq!(text(&emulator, 0), "$ less file");
+        assert_eq!(text(&emulator, 1), "$");
+        assert_eq!(emulator.cursor(), (1, 2));
+    }
+
+    #[test]
+    fn test_alt_screen_legacy_modes() {
+        for mode in [b"47", b"1047".as_slice()] {
+            let mut emulator = Emulator::new(5, 20);
+            emulator.advance(b"primary");
+            emulator.advance(&[b"\x1b[?".as_slice(), mode, b"h"].concat());
+            assert!(emulator.is_alt_screen());
+            emulator.advance(b"\x1b[2J\x1b[Halternate");
+            assert_eq!(text(&emulator, 0), "alternate");
+            emulator.advance(&[b"\x1b[?".as_slice(), mode, b"l"].concat());
+            assert!(!emulator.is_alt_screen());
+            assert_eq!(text(&emulator, 0), "primary");
+        }
+    }
+
+    #[test]
+    fn test_alt_screen_legacy_modes_keep_saved_cursor() {
+        for mode in ["47", "1047"] {
+            let mut emulator = Emulator::new(5, 20);
+            let switch = format!("\x1b7\x1b[4;10H\x1b[?{mode}h\x1b[?{mode}l\x1b8");
+            emulator.advance(format!("\x1b[2;3H{switch}").as_bytes());
+            assert_eq!(emulator.cursor(), (1, 2));
+        }
+    }
+
+    #[test]
+    fn test_save_restore_cursor_mode() {
+        let mut emulator = Emulator::new(5, 20);
+        emulator.advance(b"\x1b[3;4H\x1b[?1048h\x1b[H");
+        assert_eq!(emulator.cursor(), (0, 0));
+        emulator.advance(b"\x1b[?1048l");
+        assert_eq!(emulator.cursor(), (2, 3));
+    }
+
+    #[test]
+    fn test_alt_screen_does_not_touch_scrollback() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"one\r\ntwo\r\nthree");
+        emulator.advance(b"\x1b[?1049h\x1b[2Ja\r\nb\r\nc\r\nd\x1b[?1049l");
+        assert_eq!(text(&emulator, 0), "two");
+        assert_eq!(text(&emulator, 1), "three");
+    }
+
+    #[test]
+    fn test_scroll_region_keeps_status_line() {
+        let mut emulator = Emulator::new(4, 10);
+        emulator.advance(b"\x1b[4;1Hstatus\x1b[1;3r\x1b[1;1Ha\r\nb\r\nc\r\nd");
+        assert_eq!(screen(&emulator), ["b", "c", "d", "status"]);
+        assert_eq!(emulator.cursor(), (2, 1));
+    }
+
+    #[test]
+    fn test_scroll_region_index_and_reverse_index() {
+        let mut emulator = Emulator::new(4, 10);
+        emulator.advance(b"top\r\n1\r\n2\r\nbottom\x1b[2;3r\x1b[3;1H\x1bD");
+        assert_eq!(screen(&emulator), ["top", "2", "", "bottom"]);
+
+        emulator.advance(b"\x1b[2;1H\x1bM\x1bMx");
+        assert_eq!(screen(&emulator), ["top", "x", "", "bottom"]);
+    }
+
+    #[test]
+    fn test_scroll_region_insert_delete_lines() {
+        let mut emulator = Emulator::new(5, 10);
+        emulator.advance(b"a\r\nb\r\nc\r\nd\r\ne\x1b[2;4r\x1b[2;1H\x1b[L");
+        assert_eq!(screen(&emulator), ["a", "", "b", "c", "e"]);
+
+        emulator.advance(b"\x1b[2M");
+        assert_eq!(screen(&emulator), ["a", "c", "", "", "e"]);
+    }
+
+    #[test]
+    fn test_scroll_region_scroll_up_down() {
+        let mut emulator = Emulator::new(5, 10);
+        emulator.advance(b"a\r\nb\r\nc\r\nd\r\ne\x1b[2;4r\x1b[S");
+        assert_eq!(screen(&emulator), ["a", "c", "d", "", "e"]);
+
+        emulator.advance(b"\x1b[2T");
+        assert_eq!(screen(&emulator), ["a", "", "", "c", "e"]);
+    }
+
+    #[test]
+    fn test_scroll_region_reset() {
+        let mut emulator = Emulator::new(3, 10);
+        emulator.advance(b"\x1b[1;2r\x1b[r\x1b[3;1Ha\r\nb");
+        assert_eq!(screen(&emulator), ["", "a", "b"]);
+    }
+
+    fn row_text(cells: &[Cell]) -> String {
+        let line: String = cells.iter().map(Cell::text).collect();
+        line.trim_end().to_string()
+    }
+
+    #[test]
+    fn test_scrollback_keeps_scrolled_lines() {
+        let mut emulator = Emulator::new(2, 10);
+        assert_eq!(emulator.scrollback_lines(), DEFAULT_SCROLLBACK_LINES);
+        assert_eq!(emulator.history_len(), 0);
+        assert!(emulator.history_row(0).is_none());
+
+        emulator.advance(b"one\r\ntwo\r\nthree\r\nfour");
+        assert_eq!(emulator.history_len(), 2);
+        assert_eq!(row_text(&emulator.history_row(0).unwrap()), "two");
+        assert_eq!(row_text(&emulator.history_row(1).unwrap()), "one");
+        assert!(emulator.history_row(2).is_none());
+        assert_eq!(screen(&emulator), ["three", "four"]);
+    }
+
+    #[test]
+    fn test_scrollback_limit() {
+        let mut emulator = Emulator::with_scrollback(2, 10, 3);
+        for i in 0..10 {
+            emulator.advance(format!("line {i}\r\n").as_bytes());
+        }
+        assert_eq!(emulator.history_len(), 3);
+        assert_eq!(row_text(&emulator.history_row(0).unwrap()), "line 8");
+        assert_eq!(row_text(&emulator.history_row(2).unwrap()), "line 6");
+    }
+
+    #[test]
+    fn test_scrollback_disabled() {
+        let mut emulator = Emulator::with_scrollback(2, 10, 0);
+        emulator.advance(b"one\r\ntwo\r\nthree");
+        assert_eq!(emulator.history_len(), 0);
+    }
+
+    #[test]
+    fn test_set_scrollback_lines_shrinks_history() {
+        let mut emulator = Emulator::new(2, 10);
+        for i in 0..10 {
+            emulator.advance(format!("line {i}\r\n").as_bytes());
+        }
+        assert_eq!(emulator.history_len(), 9);
+        emulator.set_scrollback_lines(4);
+        assert_eq!(emulator.scrollback_lines(), 4);
+        assert_eq!(emulator.history_len(), 4);
+        assert_eq!(row_text(&emulator.history_row(0).unwrap()), "line 8");
+    }
+
+    #[test]
+    fn test_scroll_view_into_history() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"one\r\ntwo\r\nthree\r\nfour");
+        assert_eq!(emulator.display_offset(), 0);
+        assert_eq!(emulator.viewport_cursor(), Some((1, 4)));
+
+        emulator.scroll_lines(1);
+        assert_eq!(emulator.display_offset(), 1);
+        assert_eq!(screen(&emulator), ["two", "three"]);
+        assert_eq!(emulator.viewport_cursor(), None);
+
+        emulator.scroll_lines(10);
+        assert_eq!(emulator.display_offset(), 2);
+        assert_eq!(screen(&emulator), ["one", "two"]);
+
+        emulator.scroll_to_bottom();
+        assert_eq!(screen(&emulator), ["three", "four"]);
+
+        emulator.scroll_to_top();
+        assert_eq!(emulator.display_offset(), 2);
+        emulator.scroll_page_down();
+        assert_eq!(emulator.display_offset(), 0);
+        emulator.scroll_page_up();
+        assert_eq!(emulator.display_offset(), 2);
+    }
+
+    #[test]
+    fn test_wheel_scrolls_primary_screen() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"one\r\ntwo\r\nthree");
+        assert_eq!(emulator.wheel(3, 0, 0), None);
+        assert_eq!(emulator.display_offset(), 1);
+        assert_eq!(emulator.wheel(-1, 0, 0), None);
+        assert_eq!(emulator.display_offset(), 0);
+    }
+
+    #[test]
+    fn test_wheel_sends_arrows_on_alt_screen() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"one\r\ntwo\r\nthree\x1b[?1049h");
+        assert_eq!(emulator.wheel(2, 0, 0), Some(b"\x1b[A\x1b[A".to_vec()));
+        assert_eq!(emulator.display_offset(), 0);
+        emulator.advance(b"\x1b[?1h");
+        assert_eq!(emulator.wheel(-1, 0, 0), Some(b"\x1bOB".to_vec()));
+        emulator.advance(b"\x1b[?1007l");
+        assert_eq!(emulator.wheel(1, 0, 0), None);
+    }
+
+    #[test]
+    fn test_wheel_mouse_reports() {
+        let mut emulator = Emulator::new(5, 10);
+        emulator.advance(b"\x1b[?1000h");
+        assert_eq!(emulator.wheel(1, 2, 3), Some(b"\x1b[M`$#".to_vec()));
+        emulator.advance(b"\x1b[?1006h");
+        assert_eq!(
+            emulator.wheel(-2, 2, 3),
+            Some(b"\x1b[<65;4;3M\x1b[<65;4;3M".to_vec())
+        );
+        assert_eq!(emulator.display_offset(), 0);
+    }
+
+    #[test]
+    fn test_color_scheme_query() {
+        let mut emulator = Emulator::new(5, 10);
+        assert!(emulator.dark_mode());
+        emulator.advance(b"a\x1b[?996nb");
+        assert_eq!(emulator.take_responses(), b"\x1b[?997;1n");
+        assert!(emulator.take_responses().is_empty());
+        assert_eq!(text(&emulator, 0), "ab");
+
+        emulator.set_dark_mode(false);
+        emulator.advance(b"\x1b[?996n");
+        assert_eq!(emulator.take_responses(), b"\x1b[?997;2n");
+    }
+
+    #[test]
+    fn test_color_scheme_notifications() {
+        let mut emulator = Emulator::new(5, 10);
+        // No reports until the program asks for them
+        emulator.set_dark_mode(false);
+        assert!(emulator.take_responses().is_empty());
+
+        emulator.advance(b"\x1b[?2031h");
+        emulator.set_dark_mode(false);
+        assert!(emulator.take_responses().is_empty());
+        emulator.set_dark_mode(true);
+        assert_eq!(emulator.take_responses(), b"\x1b[?997;1n");
+
+        emulator.advance(b"\x1b[?2031l");
+        emulator.set_dark_mode(false);
+        assert!(emulator.take_responses().is_empty());
+    }
+
+    #[test]
+    fn test_synchronized_update_held_until_end() {
+        let mut emulator = Emulator::new(3, 20);
+        emulator.advance(b"before\x1b[?2026h\r\nheld");
+        assert_eq!(screen(&emulator), ["before", "", ""]);
+        emulator.advance(b"\x1b[?2026l");
+        assert_eq!(screen(&emulator), ["before", "held", ""]);
+    }
+
+    #[test]
+    fn test_synchronized_update_keeps_intercepted_order() {
+        let mut emulator = Emulator::new(3, 20);
+        emulator.advance(b"\x1b[?2026hprimary\r\n\x1b[?47h\x1b[Halt\x1b[?2026l");
+        assert!(emulator.is_alt_screen());
+        assert_eq!(text(&emulator, 0), "alt");
+        emulator.advance(b"\x1b[?47l");
+        assert_eq!(text(&emulator, 0), "primary");
+    }
+
+    #[test]
+    fn test_synchronized_update_markers_split_across_chunks() {
+        let mut emulator = Emulator::new(3, 20);
+        emulator.advance(b"a\x1b[?20");
+        emulator.advance(b"26hb\x1b");
+        assert_eq!(text(&emulator, 0), "a");
+        emulator.advance(b"[?2026l");
+        assert_eq!(text(&emulator, 0), "ab");
+
+        // A cut-off escape that turns out to be something else
+        emulator.advance(b"\x1b[?");
+        emulator.advance(b"25lc");
+        assert_eq!(text(&emulator, 0), "abc");
+        assert!(!emulator.cursor_visible());
+    }
+
+    #[test]
+    fn test_synchronized_update_among_other_modes() {
+        let mut emulator = Emulator::new(3, 20);
+        emulator.advance(b"\x1b[?25;2026lbefore\x1b[?2026;25hheld");
+        assert_eq!(text(&emulator, 0), "before");
+        assert!(emulator.cursor_visible());
+        emulator.advance(b"\x1b[?2026;1l");
+        assert_eq!(text(&emulator, 0), "beforeheld");
+    }
+
+    #[test]
+    fn test_synchronized_update_timeout() {
+        let mut emulator = Emulator::new(3, 20);
+        emulator.advance(b"\x1b[?2026hheld");
+        assert!(!emulator.flush_expired_sync());
+        assert_eq!(text(&emulator, 0), "");
+
+        emulator.sync.as_mut().unwrap().deadline = Instant::now();
+        assert!(emulator.flush_expired_sync());
+        assert_eq!(text(&emulator, 0), "held");
+        emulator.advance(b" more");
+        assert_eq!(text(&emulator, 0), "held more");
+    }
+
+    #[test]
+    fn test_synchronized_update_buffer_limit() {
+        let mut emulator = Emulator::new(3, 20);
+        emulator.advance(b"\x1b[?2026h");
+        emulator.advance(&vec![b'x'; SYNC_BUFFER_LIMIT + 1]);
+        assert!(emulator.sync.is_none());
+        assert_eq!(text(&emulator, 0), "x".repeat(20));
+    }
+
+    #[test]
+    fn test_utf8_multibyte() {
+        let mut emulator = Emulator::new(2, 20);
+        emulator.advance("Grüße ─┼─".as_bytes());
+        assert_eq!(text(&emulator, 0), "Grüße ─┼─");
+    }
+
+    #[test]
+    fn test_utf8_split_across_chunks() {
+        let mut emulator = Emulator::new(2, 20);
+        let bytes = "ä│日".as_bytes();
+        for chunk in bytes.chunks(1) {
+            emulator.advance(chunk);
+        }
+        let grid = emulator.grid();
+        assert_eq!(grid.get(0, 0).unwrap().c, 'ä');
+        assert_eq!(grid.get(0, 1).unwrap().c, '│');
+        assert_eq!(grid.get(0, 2).unwrap().c, '日');
+    }
+
+    #[test]
+    fn test_combining_characters() {
+        let mut emulator = Emulator::new(3, 10);
+        emulator.advance("Gru\u{0308}sse".as_bytes());
+        assert_eq!(text(&emulator, 0), "Gru\u{0308}sse");
+        assert_eq!(emulator.cursor(), (0, 6));
+
+        let grid = emulator.grid();
+        let cell = grid.get(0, 2).unwrap();
+        assert_eq!(cell.c, 'u');
+        assert_eq!(cell.zerowidth, ['\u{0308}']);
+        assert!(grid.get(0, 3).unwrap().zerowidth.is_empty());
+    }
+
+    #[test]
+    fn test_utf8_invalid_replaced() {
+        let mut emulator = Emulator::new(2, 20);
+        emulator.advance(b"a\xffb");
+        assert_eq!(text(&emulator, 0), "a\u{fffd}b");
+    }
+
+    #[test]
+    fn test_wide_characters() {
+        let mut emulator = Emulator::new(2, 20);
+        emulator.advance("日本x".as_bytes());
+        let grid = emulator.grid();
+        assert_eq!(grid.get(0, 0).unwrap().width, 2);
+        assert!(grid.get(0, 1).unwrap().is_wide_spacer());
+        assert_eq!(grid.get(0, 2).unwrap().c, '本');
+        assert_eq!(grid.get(0, 4).unwrap().c, 'x');
+        assert_eq!(emulator.cursor(), (0, 5));
+    }
+
+    #[test]
+    fn test_sgr_colors() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b[31;44mX\x1b[0mY\x1b[38;5;196mZ");
+        let grid = emulator.grid();
+        let x = grid.get(0, 0).unwrap();
+        assert_eq!(x.fg, CellColor::Indexed(1));
+        assert_eq!(x.bg, CellColor::Indexed(4));
+        let y = grid.get(0, 1).unwrap();
+        assert_eq!(y.fg, CellColor::Default);
+        assert_eq!(y.bg, CellColor::Default);
+        assert_eq!(grid.get(0, 2).unwrap().fg, CellColor::Indexed(196));
+    }
+
+    #[test]
+    fn test_sgr_truecolor() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b[38;2;255;128;0;48;2;10;20;30mA\x1b[0mB");
+        let grid = emulator.grid();
+        let a = grid.get(0, 0).unwrap();
+        assert_eq!(a.fg, CellColor::Rgb(Color::new(255, 128, 0)));
+        assert_eq!(a.bg, CellColor::Rgb(Color::new(10, 20, 30)));
+        assert_eq!(grid.get(0, 1).unwrap().fg, CellColor::Default);
+    }
+
+    #[test]
+    fn test_sgr_truecolor_colon_form() {
+        let mut emulator = Emulator::new(2, 10);
+        // With and without the ITU color space id
+        emulator.advance(b"\x1b[38:2::1:2:3mA\x1b[48:2:4:5:6mB\x1b[38:5:196mC");
+        let grid = emulator.grid();
+        assert_eq!(
+            grid.get(0, 0).unwrap().fg,
+            CellColor::Rgb(Color::new(1, 2, 3))
+        );
+        assert_eq!(
+            grid.get(0, 1).unwrap().bg,
+            CellColor::Rgb(Color::new(4, 5, 6))
+        );
+        assert_eq!(grid.get(0, 2).unwrap().fg, CellColor::Indexed(196));
+    }
+
+    #[test]
+    fn test_sgr_truecolor_out_of_range_ignored() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b[38;2;300;0;0mA");
+        assert_eq!(emulator.grid().get(0, 0).unwrap().fg, CellColor::Default);
+    }
+
+    #[test]
+    fn test_sgr_underline_color() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b[4;58;2;9;8;7mA\x1b[58:5:196mB\x1b[59mC");
+        let grid = emulator.grid();
+        assert_eq!(
+            grid.get(0, 0).unwrap().underline_color,
+            Some(CellColor::Rgb(Color::new(9, 8, 7)))
+        );
+        assert_eq!(
+            grid.get(0, 1).unwrap().underline_color,
+            Some(CellColor::Indexed(196))
+        );
+        assert_eq!(grid.get(0, 2).unwrap().underline_color, None);
+    }
+
+    #[test]
+    fn test_sgr_styles() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b[1;3;4mA\x1b[22;23;24mB");
+        let grid = emulator.grid();
+        let a = grid.get(0, 0).unwrap().style;
+        assert!(a.bold && a.italic && a.underline);
+        assert!(!grid.get(0, 1).unwrap().style.has_any());
+    }
+
+    #[test]
+    fn test_sgr_inverse_hidden_strike() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b[7;8;9mA\x1b[27mB\x1b[28mC\x1b[29mD");
+        let grid = emulator.grid();
+        let a = grid.get(0, 0).unwrap().style;
+        assert!(a.inverse && a.hidden && a.strikethrough);
+        let b = grid.get(0, 1).unwrap().style;
+        assert!(!b.inverse && b.hidden && b.strikethrough);
+        let c = grid.get(0, 2).unwrap().style;
+        assert!(!c.hidden && c.strikethrough);
+        assert!(!grid.get(0, 3).unwrap().style.has_any());
+    }
+
+    #[test]
+    fn test_sgr_underline_styles() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b[4mA\x1b[21mB\x1b[4:3mC\x1b[4:4mD\x1b[4:5mE\x1b[4:0mF");
+        let grid = emulator.grid();
+        let styles: Vec<_> = (0..5).map(|col| grid.get(0, col).unwrap().style).collect();
+        assert!(styles.iter().all(|style| style.underline));
+        assert_eq!(styles[0].underline_style, UnderlineStyle::Single);
+        assert_eq!(styles[1].underline_style, UnderlineStyle::Double);
+        assert_eq!(styles[2].underline_style, UnderlineStyle::Curly);
+        assert_eq!(styles[3].underline_style, UnderlineStyle::Dotted);
+        assert_eq!(styles[4].underline_style, UnderlineStyle::Dashed);
+        assert!(!grid.get(0, 5).unwrap().style.underline);
+    }
+
+    #[test]
+    fn test_sgr_blink_and_overline() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b[5;53mA\x1b[25mB\x1b[55mC\x1b[6mD\x1b[0mE");
+        let grid = emulator.grid();
+        let a = grid.get(0, 0).unwrap().style;
+        assert!(a.blink && a.overline);
+        let b = grid.get(0, 1).unwrap().style;
+        assert!(!b.blink && b.overline);
+        assert!(!grid.get(0, 2).unwrap().style.has_any());
+        assert!(grid.get(0, 3).unwrap().style.blink);
+        assert!(!grid.get(0, 4).unwrap().style.blink);
+    }
+
+    #[test]
+    fn test_sgr_attribute_order() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b[5;0mA\x1b[0;53mB\x1b[38;5;5mC\x1b[38;2;5;53;5mD");
+        let grid = emulator.grid();
+        assert!(!grid.get(0, 0).unwrap().style.blink);
+        assert!(grid.get(0, 1).unwrap().style.overline);
+        // Color arguments are not attributes
+        let c = grid.get(0, 2).unwrap().style;
+        assert!(!c.blink && c.overline);
+        assert!(!grid.get(0, 3).unwrap().style.blink);
+    }
+
+    #[test]
+    fn test_sgr_21_keeps_bold() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b[1m\x1b[21mA\x1b[1;21;22mB\x1b[21;24mC\x1b[1;21;4mD");
+        let grid = emulator.grid();
+        let a = grid.get(0, 0).unwrap().style;
+        assert!(a.bold && a.underline_style == UnderlineStyle::Double);
+        let b = grid.get(0, 1).unwrap().style;
+        assert!(!b.bold && b.underline_style == UnderlineStyle::Double);
+        assert!(!grid.get(0, 2).unwrap().style.underline);
+        let d = grid.get(0, 3).unwrap().style;
+        assert!(d.bold && d.underline_style == UnderlineStyle::Single);
+    }
+
+    #[test]
+    fn test_sgr_marks_keep_underline_color() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b[4;58;2;1;2;3m\x1b[53mA\x1b[55mB");
+        let grid = emulator.grid();
+        assert_eq!(
+            grid.get(0, 0).unwrap().underline_color,
+            Some(CellColor::Rgb(Color::new(1, 2, 3)))
+        );
+        assert_eq!(
+            grid.get(0, 1).unwrap().underline_color,
+            Some(CellColor::Rgb(Color::new(1, 2, 3)))
+        );
+    }
+
+    #[test]
+    fn test_sgr_underline_color_keeps_marks() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b[53mA\x1b[58;5;1mB\x1b[59mC\x1b[0mD");
+        let grid = emulator.grid();
+        assert!((0..3).all(|col| grid.get(0, col).unwrap().style.overline));
+        assert_eq!(
+            grid.get(0, 1).unwrap().underline_color,
+            Some(CellColor::Indexed(1))
+        );
+        assert!(!grid.get(0, 3).unwrap().style.overline);
+    }
+
+    #[test]
+    fn test_hyperlink_keeps_marks() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b[5m\x1b]8;;https://example.com\x1b\\A\x1b]8;;\x1b\\B");
+        let grid = emulator.grid();
+        assert!(grid.get(0, 0).unwrap().style.blink);
+        assert!(grid.get(0, 1).unwrap().style.blink);
+    }
+
+    #[test]
+    fn test_marks_with_combining_characters() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance("\x1b[5;53mu\u{0308}\x1b[0mo\u{0308}".as_bytes());
+        let grid = emulator.grid();
+        let u = grid.get(0, 0).unwrap();
+        assert_eq!(u.zerowidth, ['\u{0308}']);
+        assert!(u.style.blink && u.style.overline);
+        let o = grid.get(0, 1).unwrap();
+        assert_eq!(o.zerowidth, ['\u{0308}']);
+        assert!(!o.style.has_any());
+    }
+
+    #[test]
+    fn test_marks_with_combining_character_after_reset() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance("\x1b[5;53mu\x1b[0m\u{0308}".as_bytes());
+        let grid = emulator.grid();
+        let u = grid.get(0, 0).unwrap();
+        assert_eq!(u.zerowidth, ['\u{0308}']);
+        assert!(u.style.blink && u.style.overline);
+    }
+
+    #[test]
+    fn test_marks_in_synchronized_update() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b[?2026hA\x1b[53mB\x1b[55mC\x1b[?2026l");
+        let grid = emulator.grid();
+        assert!(!grid.get(0, 0).unwrap().style.overline);
+        assert!(grid.get(0, 1).unwrap().style.overline);
+        assert!(!grid.get(0, 2).unwrap().style.overline);
+    }
+
+    #[test]
+    fn test_restore_cursor_restores_marks() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b[53m\x1b7\x1b[55m\x1b[2CA\x1b8B");
+        let grid = emulator.grid();
+        assert!(!grid.get(0, 2).unwrap().style.overline);
+        assert!(grid.get(0, 0).unwrap().style.overline);
+    }
+
+    #[test]
+    fn test_erase_drops_attribute_marks() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b[5;53m\x1b[2K");
+        assert!(!emulator.grid().get(0, 5).unwrap().style.has_any());
+    }
+
+    #[test]
+    fn test_palette_override() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b]4;1;rgb:12/34/56\x07\x1b[31mA\x1b[32mB");
+        let grid = emulator.grid();
+        assert_eq!(
+            grid.get(0, 0).unwrap().fg,
+            CellColor::Rgb(Color::new(0x12, 0x34, 0x56))
+        );
+        assert_eq!(grid.get(0, 1).unwrap().fg, CellColor::Indexed(2));
+    }
+
+    fn reply(emulator: &mut Emulator, query: &[u8]) -> String {
+        emulator.advance(query);
+        String::from_utf8(emulator.take_responses()).unwrap()
+    }
+
+    #[test]
+    fn test_title_and_icon_name() {
+        let mut emulator = Emulator::new(2, 10);
+        assert_eq!(emulator.title(), "");
+        emulator.advance(b"\x1b]0;vim main.rs\x07");
+        assert_eq!(emulator.title(), "vim main.rs");
+        assert_eq!(emulator.icon_name(), "vim main.rs");
+
+        emulator.advance(b"\x1b]2;~/src; ls\x1b\\\x1b]1;vim\x07");
+        assert_eq!(emulator.title(), "~/src; ls");
+        assert_eq!(emulator.icon_name(), "vim");
+
+        // Titles don't reach the screen
+        assert_eq!(text(&emulator, 0), "");
+    }
+
+    #[test]
+    fn test_title_stack() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b]0;shell\x07\x1b[22;0t\x1b]0;vim\x07");
+        emulator.advance(b"\x1b[23;0t");
+        assert_eq!((emulator.title(), emulator.icon_name()), ("shell", "shell"));
+
+        // Title and icon name are saved and restored separately
+        emulator.advance(b"\x1b[22;2t\x1b]0;top\x07\x1b[23;1t");
+        assert_eq!((emulator.title(), emulator.icon_name()), ("top", "top"));
+        emulator.advance(b"\x1b[23;2t");
+        assert_eq!((emulator.title(), emulator.icon_name()), ("shell", "top"));
+
+        // Popping an empty stack keeps the current title
+        emulator.advance(b"\x1b[23t");
+        assert_eq!(emulator.title(), "shell");
+    }
+
+    #[test]
+    fn test_title_stack_depth() {
+        let mut emulator = Emulator::new(2, 10);
+        for _ in 0..=TITLE_STACK_DEPTH {
+            emulator.advance(b"\x1b[22;2t");
+        }
+        assert_eq!(emulator.title_stack.len(), TITLE_STACK_DEPTH);
+    }
+
+    #[test]
+    fn test_device_attributes() {
+        let mut emulator = Emulator::new(5, 10);
+        assert_eq!(reply(&mut emulator, b"\x1b[c"), "\x1b[?6c");
+        assert_eq!(reply(&mut emulator, b"\x1b[0c"), "\x1b[?6c");
+        assert!(reply(&mut emulator, b"\x1b[>c").starts_with("\x1b[>0;"));
+    }
+
+    #[test]
+    fn test_device_status_reports() {
+        let mut emulator = Emulator::new(5, 10);
+        assert_eq!(reply(&mut emulator, b"\x1b[5n"), "\x1b[0n");
+        assert_eq!(reply(&mut emulator, b"\x1b[3;7H\x1b[6n"), "\x1b[3;7R");
+        // Replies keep the order of the queries
+        assert_eq!(
+            reply(&mut emulator, b"ab\x1b[6n\x1b[?996n\x1b[6n"),
+            "\x1b[3;9R\x1b[?997;1n\x1b[3;9R"
+        );
+    }
+
+    #[test]
+    fn test_version_report() {
+        let mut emulator = Emulator::new(5, 10);
+        let expected = format!(
+            "\x1bP>|dioxus-terminal({})\x1b\\",
+            env!("CARGO_PKG_VERSION")
+        );
+        assert_eq!(reply(&mut emulator, b"\x1b[>q"), expected);
+        assert_eq!(reply(&mut emulator, b"\x1b[>0q"), expected);
+    }
+
+    #[test]
+    fn test_mode_reports() {
+        let mut emulator = Emulator::new(5, 10);
+        assert_eq!(reply(&mut emulator, b"\x1b[?25$p"), "\x1b[?25;1$y");
+        assert_eq!(reply(&mut emulator, b"\x1b[?25l\x1b[?25$p"), "\x1b[?25;2$y");
+        assert_eq!(reply(&mut emulator, b"\x1b[4$p"), "\x1b[4;2$y");
+        assert_eq!(reply(&mut emulator, b"\x1b[?9999$p"), "\x1b[?9999;0$y");
+
+        // Modes handled on top of alacritty_terminal report their own state
+        assert_eq!(reply(&mut emulator, b"\x1b[?2031$p"), "\x1b[?2031;2$y");
+        assert_eq!(
+            reply(&mut emulator, b"\x1b[?2031h\x1b[?2031$p"),
+            "\x1b[?2031;1$y"
+        );
+        assert_eq!(
+            reply(&mut emulator, b"\x1b[?1047h\x1b[?1047$p\x1b[?47$p"),
+            "\x1b[?1047;1$y\x1b[?47;1$y"
+        );
+        assert_eq!(
+            reply(
+                &mut emulator,
+                b"\x1b[?2026h\x1b[?2031l\x1b[?2031$p\x1b[?2026l"
+            ),
+            "\x1b[?2031;2$y"
+        );
+    }
+
+    #[test]
+    fn test_color_queries_answered_from_theme() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.set_theme(Theme::gruvbox());
+        emulator.advance(b"\x1b]11;?\x07\x1b]10;?\x1b\\\x1b]4;1;?\x07");
+        assert_eq!(
+            String::from_utf8(emulator.take_responses()).unwrap(),
+            "\x1b]11;rgb:2828/2828/2828\x07\x1b]10;rgb:ebeb/dbdb/b2b2\x1b\\\x1b]4;1;rgb:cccc/2424/1d1d\x07"
+        );
+
+        // The cursor defaults to the foreground
+        emulator.advance(b"\x1b]12;?\x07");
+        assert_eq!(emulator.take_responses(), b"\x1b]12;rgb:ebeb/dbdb/b2b2\x07");
+    }
+
+    #[test]
+    fn test_highlight_queries() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b]17;?\x07\x1b]19;?\x1b\\");
+        assert_eq!(
+            String::from_utf8(emulator.take_responses()).unwrap(),
+            "\x1b]17;rgb:cccc/cccc/cccc\x07\x1b]19;rgb:0000/0000/0000\x1b\\"
+        );
+
+        let mut theme = Theme::dark();
+        theme.selection = Some(Color::new(0x11, 0x22, 0x33));
+        emulator.set_theme(theme);
+        emulator.advance(b"\x1b]17;?\x07\x1b]19;?\x07");
+        assert_eq!(
+            String::from_utf8(emulator.take_responses()).unwrap(),
+            "\x1b]17;rgb:1111/2222/3333\x07\x1b]19;rgb:cccc/cccc/cccc\x07"
+        );
+    }
+
+    #[test]
+    fn test_color_overrides_set_and_reset() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b]11;rgb:12/34/56\x07\x1b]4;2;#abcdef\x07");
+        emulator.advance(b"\x1b]11;?\x07\x1b]4;2;?\x07");
+        assert_eq!(
+            String::from_utf8(emulator.take_responses()).unwrap(),
+            "\x1b]11;rgb:1212/3434/5656\x07\x1b]4;2;rgb:abab/cdcd/efef\x07"
+        );
+        // Cells with the default background show the override
+        assert_eq!(
+            emulator.grid().get(0, 0).unwrap().bg,
+            CellColor::Rgb(Color::new(0x12, 0x34, 0x56))
+        );
+
+        emulator.advance(b"\x1b]111\x07\x1b]104\x07");
+        emulator.advance(b"\x1b]11;?\x07\x1b]4;2;?\x07");
+        assert_eq!(
+            String::from_utf8(emulator.take_responses()).unwrap(),
+            "\x1b]11;rgb:0000/0000/0000\x07\x1b]4;2;rgb:0d0d/bcbc/7979\x07"
+        );
+        assert_eq!(emulator.grid().get(0, 0).unwrap().bg, CellColor::Default);
+    }
+
+    #[test]
+    fn test_set_theme_updates_dark_mode() {
+        let mut emulator = Emulator::new(2, 10);
+        emulator.advance(b"\x1b[?2031h");
+        emulator.set_theme(Theme::light());
+        assert!(!emulator.dark_mode());
+        assert_eq!(emulator.take_responses(), b"\x1b[?997;2n");
+    }
+}
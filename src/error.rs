@@ -21,9 +21,17 @@ pub enum Error {
     #[error("invalid terminal size: {rows}x{cols}")]
     InvalidSize { rows: u16, cols: u16 },
 
+    /// Failed to resize PTY
+    #[error("failed to resize PTY: {0}")]
+    Resize(String),
+
     /// Command not found
     #[error("command not found: {0}")]
     CommandNotFound(String),
+
+    /// Malformed theme file
+    #[error("invalid theme: {0}")]
+    InvalidTheme(String),
 }
 
 #[cfg(test)]
@@ -51,6 +59,21 @@ mod tests {
         assert_eq!(err.to_string(), "invalid terminal size: 0x80");
     }
 
+    #[test]
+    fn error_display_invalid_theme() {
+        let err = Error::InvalidTheme("kitty line 2: invalid color `#12`".to_string());
+        assert_eq!(
+            err.to_string(),
+            "invalid theme: kitty line 2: invalid color `#12`"
+        );
+    }
+
+    #[test]
+    fn error_display_resize() {
+        let err = Error::Resize("bad file descriptor".to_string());
+        assert_eq!(err.to_string(), "failed to resize PTY: bad file descriptor");
+    }
+
     #[test]
     fn error_display_command_not_found() {
         let err = Error::CommandNotFound("zsh".to_string());
@@ -8,9 +8,20 @@
 //! ## Features
 //!
 //! - Terminal emulation (VT100/xterm compatible)
-//! - ANSI color support (16 and 256 colors)
+//! - Scrollback history with a configurable limit, scrolled with the mouse wheel or Shift+PageUp/PageDown
+//! - ANSI color support (16, 256 and 24-bit truecolor)
+//! - All SGR text attributes, including curly, dotted and dashed underlines with their own color
+//! - UTF-8 output, including wide (CJK/emoji) characters
 //! - Keyboard input
-//! - Customizable themes
+//! - Process control: exit status, signals and graceful termination
+//! - Window title and icon name (OSC 0/1/2, with the XTWINOPS 22/23 title stack)
+//! - Optional fit mode that sizes rows and columns to the container
+//! - Customizable themes with full 16-color ANSI palettes
+//! - Automatic light/dark theme following the desktop, reported to programs (`CSI ? 996 n`, DSR 997)
+//! - Color queries (OSC 4, 10, 11, 12, 17, 19) answered from the active theme, with per-session overrides
+//! - Replies to device queries: attributes (DA1/DA2), status and cursor position (DSR 5/6), modes (DECRQM) and version (XTVERSION)
+//! - Optional theme import from Alacritty, kitty, iTerm2, Windows Terminal and base16/base24 files
+//!   (`theme-import` feature)
 //!
 //! ## Example
 //!
@@ -43,17 +54,19 @@
 //! }
 //! ```
 
+mod emulator;
 mod error;
 mod pty;
 mod term;
 mod theme;
 mod widget;
 
+pub use emulator::{DEFAULT_SCROLLBACK_LINES, Emulator};
 pub use error::Error;
-pub use pty::Pty;
-pub use term::{Cell, Color, Grid, Style};
-pub use theme::Theme;
-pub use widget::{DEFAULT_FONT_FAMILY, Terminal, TerminalProps};
+pub use pty::{DEFAULT_TERM, ExitStatus, Pty, PtyBuilder, PtySignal};
+pub use term::{Cell, CellColor, Color, Grid, Style, UnderlineStyle};
+pub use theme::{Theme, ThemeMode};
+pub use widget::{DEFAULT_FONT_FAMILY, Terminal, TerminalHandle, TerminalProps};
 
 /// Result type for dioxus-terminal operations
 pub type Result<T> = std::result::Result<T, Error>;
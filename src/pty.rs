@@ -1,23 +1,302 @@
 //! PTY (pseudo-terminal) management
 
-use portable_pty::{CommandBuilder, PtySize, native_pty_system};
+use portable_pty::{Child, CommandBuilder, MasterPty, PtySize, native_pty_system};
+use std::ffi::OsString;
 use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+#[cfg(unix)]
+use std::time::Instant;
 use tokio::sync::mpsc;
 
 use crate::{Error, Result};
 
+pub use portable_pty::ExitStatus;
+
+/// Signals that can be delivered to the running process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PtySignal {
+    /// `SIGINT`, as sent by Ctrl+C
+    Interrupt,
+    /// `SIGTERM`, a request to exit
+    Terminate,
+    /// `SIGHUP`, the terminal was closed
+    Hangup,
+    /// `SIGKILL`, exit immediately
+    Kill,
+    /// `SIGTSTP`, as sent by Ctrl+Z
+    Stop,
+    /// `SIGCONT`, resume a stopped process
+    Continue,
+}
+
+#[cfg(unix)]
+impl PtySignal {
+    /// Get the platform signal number
+    const fn as_raw(self) -> libc::c_int {
+        match self {
+            Self::Interrupt => libc::SIGINT,
+            Self::Terminate => libc::SIGTERM,
+            Self::Hangup => libc::SIGHUP,
+            Self::Kill => libc::SIGKILL,
+            Self::Stop => libc::SIGTSTP,
+            Self::Continue => libc::SIGCONT,
+        }
+    }
+}
+
 /// PTY handle for terminal I/O
 pub struct Pty {
+    master: Box<dyn MasterPty + Send>,
+    child: Box<dyn Child + Send + Sync>,
+    exit_status: Option<ExitStatus>,
     writer: Arc<Mutex<Box<dyn Write + Send>>>,
     reader_rx: mpsc::Receiver<Vec<u8>>,
     size: PtySize,
 }
 
+/// Default value of `TERM` for spawned programs
+pub const DEFAULT_TERM: &str = "xterm-256color";
+
+/// How long a dropped [`Pty`] gives its process to exit after hanging up
+#[cfg(unix)]
+const HANGUP_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Builder for spawning a [`Pty`] with a custom working directory and environment
+///
+/// Programs get `TERM` (default [`DEFAULT_TERM`]) and `COLORTERM=truecolor`
+/// unless overridden with [`PtyBuilder::env`] or removed with
+/// [`PtyBuilder::env_remove`].
+#[derive(Debug, Clone)]
+pub struct PtyBuilder {
+    command: String,
+    args: Vec<String>,
+    rows: u16,
+    cols: u16,
+    cwd: Option<PathBuf>,
+    env: Vec<(String, Option<String>)>,
+    env_clear: bool,
+    term: String,
+}
+
+impl PtyBuilder {
+    /// Create a builder for running `command` in a 24x80 terminal
+    #[must_use]
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            args: Vec::new(),
+            rows: 24,
+            cols: 80,
+            cwd: None,
+            env: Vec::new(),
+            env_clear: false,
+            term: DEFAULT_TERM.to_string(),
+        }
+    }
+
+    /// Add an argument
+    #[must_use]
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Add several arguments
+    #[must_use]
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set the terminal size
+    #[must_use]
+    pub const fn size(mut self, rows: u16, cols: u16) -> Self {
+        self.rows = rows;
+        self.cols = cols;
+        self
+    }
+
+    /// Set the working directory (default: the home directory)
+    #[must_use]
+    pub fn cwd(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cwd = Some(dir.into());
+        self
+    }
+
+    /// Set an environment variable
+    #[must_use]
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), Some(value.into())));
+        self
+    }
+
+    /// Remove an environment variable
+    #[must_use]
+    pub fn env_remove(mut self, key: impl Into<String>) -> Self {
+        self.env.push((key.into(), None));
+        self
+    }
+
+    /// Start from an empty environment instead of inheriting this process's
+    #[must_use]
+    pub const fn env_clear(mut self) -> Self {
+        self.env_clear = true;
+        self
+    }
+
+    /// Set the `TERM` value (default: [`DEFAULT_TERM`])
+    #[must_use]
+    pub fn term(mut self, term: impl Into<String>) -> Self {
+        self.term = term.into();
+        self
+    }
+
+    /// Create the PTY and spawn the command
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CommandNotFound`] if the command is not an executable
+    /// file or cannot be found on `PATH`, and other errors if the size is
+    /// invalid, the working directory does not exist, or the PTY cannot be
+    /// created or the command fails to spawn.
+    pub fn spawn(self) -> Result<Pty> {
+        if let Some(ref dir) = self.cwd {
+            // portable-pty silently falls back to the home directory
+            if !dir.is_dir() {
+                return Err(Error::SpawnCommand(format!(
+                    "no such directory: {}",
+                    dir.display()
+                )));
+            }
+        }
+        if self.resolve_command().is_none() {
+            return Err(Error::CommandNotFound(self.command));
+        }
+
+        let mut cmd = CommandBuilder::new(&self.command);
+        cmd.args(&self.args);
+        if let Some(ref dir) = self.cwd {
+            cmd.cwd(dir);
+        }
+
+        if self.env_clear {
+            cmd.env_clear();
+        }
+        cmd.env("TERM", &self.term);
+        cmd.env("COLORTERM", "truecolor");
+        for (key, value) in &self.env {
+            match value {
+                Some(value) => cmd.env(key, value),
+                None => cmd.env_remove(key),
+            }
+        }
+
+        Pty::open(cmd, self.rows, self.cols)
+    }
+
+    /// Find the executable that will run, searching `PATH` for bare names
+    ///
+    /// Like portable-pty, relative commands and `PATH` entries are taken
+    /// from the directory the command starts in.
+    fn resolve_command(&self) -> Option<PathBuf> {
+        let command = Path::new(&self.command);
+        if command.is_absolute() {
+            return is_executable(command).then(|| command.to_path_buf());
+        }
+
+        let base = self.start_dir();
+        if matches!(
+            command.components().next(),
+            Some(Component::CurDir | Component::ParentDir)
+        ) {
+            let path = base.join(command);
+            return is_executable(&path).then_some(path);
+        }
+
+        std::env::split_paths(&self.var("PATH")?)
+            .map(|dir| base.join(dir).join(command))
+            .find(|path| is_executable(path))
+    }
+
+    /// Get the directory the command starts in: `cwd`, or else the home directory
+    fn start_dir(&self) -> PathBuf {
+        self.cwd.clone().unwrap_or_else(|| self.home_dir())
+    }
+
+    /// Get the home directory portable-pty starts commands in
+    #[cfg(unix)]
+    fn home_dir(&self) -> PathBuf {
+        if let Some(home) = self.var("HOME") {
+            return PathBuf::from(home);
+        }
+        // SAFETY: the entry is only read before any other call can replace it
+        let entry = unsafe { libc::getpwuid(libc::getuid()) };
+        // SAFETY: a non-null entry points to a valid passwd struct
+        if entry.is_null() || unsafe { (*entry).pw_dir.is_null() } {
+            return PathBuf::from("/");
+        }
+        // SAFETY: `pw_dir` of a non-null entry is a valid C string
+        let dir = unsafe { std::ffi::CStr::from_ptr((*entry).pw_dir) };
+        PathBuf::from(dir.to_string_lossy().into_owned())
+    }
+
+    /// Get the home directory portable-pty starts commands in
+    #[cfg(not(unix))]
+    fn home_dir(&self) -> PathBuf {
+        self.var("USERPROFILE")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_dir())
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_default()
+    }
+
+    /// Get an environment variable as the command will see it
+    fn var(&self, key: &str) -> Option<OsString> {
+        match self.env.iter().rev().find(|(name, _)| name == key) {
+            Some((_, value)) => value.as_ref().map(OsString::from),
+            None if self.env_clear => None,
+            None => std::env::var_os(key),
+        }
+    }
+}
+
+/// Check whether `path` is a file that can be executed
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        // Windows also runs `name` as `name.exe` and the like
+        let extensions =
+            std::env::var_os("PATHEXT").unwrap_or_else(|| OsString::from(".COM;.EXE;.BAT;.CMD"));
+        path.is_file()
+            || extensions
+                .to_string_lossy()
+                .split(';')
+                .filter(|ext| !ext.is_empty())
+                .any(|ext| {
+                    let mut file = path.as_os_str().to_owned();
+                    file.push(ext);
+                    Path::new(&file).is_file()
+                })
+    }
+}
+
 impl std::fmt::Debug for Pty {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Pty")
             .field("size", &self.size)
+            .field("exit_status", &self.exit_status)
             .finish_non_exhaustive()
     }
 }
@@ -32,10 +311,20 @@ impl Pty {
     /// * `rows` - Terminal height in rows
     /// * `cols` - Terminal width in columns
     ///
+    /// Use [`PtyBuilder`] to also set the working directory or environment.
+    ///
     /// # Errors
     ///
     /// Returns an error if the PTY cannot be created or the command fails to spawn.
     pub fn spawn(command: &str, args: &[&str], rows: u16, cols: u16) -> Result<Self> {
+        PtyBuilder::new(command)
+            .args(args.iter().copied())
+            .size(rows, cols)
+            .spawn()
+    }
+
+    /// Open a PTY of the given size and run `cmd` in it
+    fn open(cmd: CommandBuilder, rows: u16, cols: u16) -> Result<Self> {
         if rows == 0 || cols == 0 {
             return Err(Error::InvalidSize { rows, cols });
         }
@@ -52,10 +341,7 @@ impl Pty {
             .openpty(size)
             .map_err(|e| Error::PtyCreation(e.to_string()))?;
 
-        let mut cmd = CommandBuilder::new(command);
-        cmd.args(args);
-
-        let _child = pair
+        let child = pair
             .slave
             .spawn_command(cmd)
             .map_err(|e| Error::SpawnCommand(e.to_string()))?;
@@ -87,6 +373,9 @@ impl Pty {
         });
 
         Ok(Self {
+            master: pair.master,
+            child,
+            exit_status: None,
             writer: Arc::new(Mutex::new(writer)),
             reader_rx: rx,
             size,
@@ -128,18 +417,153 @@ impl Pty {
         self.reader_rx.recv().await
     }
 
+    /// Check whether the process has exited, without blocking
+    ///
+    /// Returns `None` while the process is still running.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the process status cannot be queried.
+    pub fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
+        if self.exit_status.is_none() {
+            self.exit_status = self.child.try_wait()?;
+        }
+        Ok(self.exit_status.clone())
+    }
+
+    /// Block until the process exits
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if waiting for the process fails.
+    pub fn wait(&mut self) -> Result<ExitStatus> {
+        if let Some(ref status) = self.exit_status {
+            return Ok(status.clone());
+        }
+        let status = self.child.wait()?;
+        self.exit_status = Some(status.clone());
+        Ok(status)
+    }
+
+    /// Get the exit status, if the process has been seen to exit
+    ///
+    /// Updated by [`Pty::try_wait`] and [`Pty::wait`].
+    #[must_use]
+    pub const fn exit_status(&self) -> Option<&ExitStatus> {
+        self.exit_status.as_ref()
+    }
+
+    /// Send a signal to the program in the terminal's foreground
+    ///
+    /// This is the process group of the job a shell is running, or the
+    /// process itself. [`PtySignal::Terminate`], [`PtySignal::Hangup`] and
+    /// [`PtySignal::Kill`] also reach the process group of the process, so they
+    /// end the shell along with its job. Does nothing if the process has
+    /// already exited.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the signal cannot be delivered, or on platforms
+    /// without signals for anything other than [`PtySignal::Kill`].
+    pub fn signal(&mut self, signal: PtySignal) -> Result<()> {
+        if self.try_wait()?.is_some() {
+            return Ok(());
+        }
+        self.send_signal(signal)
+    }
+
+    #[cfg(unix)]
+    fn send_signal(&mut self, signal: PtySignal) -> Result<()> {
+        let Some(pid) = self.pid() else {
+            return Ok(());
+        };
+
+        // The child leads its own session, so its pid is also its process
+        // group; jobs started from a shell get groups of their own
+        let foreground = self.master.process_group_leader().unwrap_or(pid);
+        kill_group(foreground, signal)?;
+        let ends_session = matches!(
+            signal,
+            PtySignal::Terminate | PtySignal::Hangup | PtySignal::Kill
+        );
+        if ends_session && foreground != pid {
+            kill_group(pid, signal)?;
+        }
+        Ok(())
+    }
+
+    /// Get the process id of the running process
+    #[cfg(unix)]
+    fn pid(&self) -> Option<libc::pid_t> {
+        self.child
+            .process_id()
+            .and_then(|pid| libc::pid_t::try_from(pid).ok())
+    }
+
+    #[cfg(not(unix))]
+    fn send_signal(&mut self, signal: PtySignal) -> Result<()> {
+        match signal {
+            PtySignal::Kill => Ok(self.child.kill()?),
+            _ => Err(std::io::Error::from(std::io::ErrorKind::Unsupported).into()),
+        }
+    }
+
+    /// Kill the running process group immediately
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the signal cannot be delivered.
+    pub fn kill(&mut self) -> Result<()> {
+        self.signal(PtySignal::Kill)
+    }
+
+    /// Ask the process to exit, killing it if it is still running after `timeout`
+    ///
+    /// Sends [`PtySignal::Terminate`], waits up to `timeout` for the process to
+    /// exit, then escalates to [`PtySignal::Kill`]. Platforms without
+    /// `SIGTERM` kill the process at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a signal cannot be delivered or waiting fails.
+    pub fn terminate(&mut self, timeout: Duration) -> Result<ExitStatus> {
+        #[cfg(unix)]
+        {
+            self.signal(PtySignal::Terminate)?;
+            if let Some(status) = wait_for_exit(timeout, || self.try_wait())? {
+                return Ok(status);
+            }
+            self.kill()?;
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = timeout;
+            self.child.kill()?;
+        }
+        self.wait()
+    }
+
     /// Resize the PTY
     ///
+    /// The running process is notified of the new size via `SIGWINCH`.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the size is invalid.
+    /// Returns an error if the size is invalid or the PTY cannot be resized.
     pub fn resize(&mut self, rows: u16, cols: u16) -> Result<()> {
         if rows == 0 || cols == 0 {
             return Err(Error::InvalidSize { rows, cols });
         }
-        self.size.rows = rows;
-        self.size.cols = cols;
-        // Note: actual resize would need master.resize() which requires keeping master handle
+        let size = PtySize {
+            rows,
+            cols,
+            ..self.size
+        };
+        self.master
+            .resize(size)
+            .map_err(|e| Error::Resize(e.to_string()))?;
+        self.size = size;
         Ok(())
     }
 
@@ -150,9 +574,108 @@ impl Pty {
     }
 }
 
+impl Drop for Pty {
+    /// Hang up the process group, as closing a terminal window would
+    ///
+    /// A process still running a second later is killed. The wait
+    /// happens on a background thread, which also reaps the process so it
+    /// does not linger as a zombie.
+    fn drop(&mut self) {
+        if !matches!(self.try_wait(), Ok(None)) {
+            return;
+        }
+
+        #[cfg(unix)]
+        if let Some(pid) = self.pid() {
+            let foreground = self.master.process_group_leader().unwrap_or(pid);
+            let _ = self.send_signal(PtySignal::Hangup);
+            std::thread::spawn(move || {
+                let reaped = || {
+                    // SAFETY: waitpid accepts a null status pointer
+                    let reaped = unsafe { libc::waitpid(pid, std::ptr::null_mut(), libc::WNOHANG) };
+                    Ok((reaped != 0).then_some(()))
+                };
+                if let Ok(None) = wait_for_exit(HANGUP_TIMEOUT, reaped) {
+                    let _ = kill_group(foreground, PtySignal::Kill);
+                    let _ = kill_group(pid, PtySignal::Kill);
+                    // SAFETY: as above
+                    unsafe { libc::waitpid(pid, std::ptr::null_mut(), 0) };
+                }
+            });
+        }
+
+        #[cfg(not(unix))]
+        let _ = self.child.kill();
+    }
+}
+
+/// Poll `exited` until it reports an exit, giving up after `timeout`
+#[cfg(unix)]
+fn wait_for_exit<T>(
+    timeout: Duration,
+    mut exited: impl FnMut() -> Result<Option<T>>,
+) -> Result<Option<T>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = exited()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Send a signal to a process group, ignoring groups that are gone
+#[cfg(unix)]
+fn kill_group(group: libc::pid_t, signal: PtySignal) -> Result<()> {
+    // SAFETY: killpg only reads its arguments
+    if unsafe { libc::killpg(group, signal.as_raw()) } == 0 {
+        return Ok(());
+    }
+    let err = std::io::Error::last_os_error();
+    if err.raw_os_error() == Some(libc::ESRCH) {
+        return Ok(());
+    }
+    Err(err.into())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Instant;
+
+    /// Read output until it contains `needle`
+    fn read_until(pty: &mut Pty, needle: &str) -> String {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut output = Vec::new();
+        while !String::from_utf8_lossy(&output).contains(needle) {
+            assert!(Instant::now() < deadline, "never saw {needle:?}");
+            match pty.try_read() {
+                Some(bytes) => output.extend(bytes),
+                None => std::thread::sleep(Duration::from_millis(10)),
+            }
+        }
+        String::from_utf8_lossy(&output).into_owned()
+    }
+
+    /// Read output until the PTY closes
+    fn read_all(pty: &mut Pty) -> String {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut output = Vec::new();
+        loop {
+            assert!(Instant::now() < deadline, "PTY never closed");
+            match pty.reader_rx.try_recv() {
+                Ok(bytes) => output.extend(bytes),
+                Err(mpsc::error::TryRecvError::Empty) => {
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                Err(mpsc::error::TryRecvError::Disconnected) => break,
+            }
+        }
+        String::from_utf8_lossy(&output).into_owned()
+    }
 
     #[test]
     fn test_invalid_size_zero_rows() {
@@ -182,6 +705,327 @@ mod tests {
         assert_eq!(pty.size(), (24, 80));
     }
 
+    #[test]
+    fn test_builder_args() {
+        let mut pty = PtyBuilder::new("echo")
+            .arg("one")
+            .args(["two", "three"])
+            .spawn()
+            .unwrap();
+        assert_eq!(pty.size(), (24, 80));
+        assert!(read_all(&mut pty).contains("one two three"));
+    }
+
+    #[test]
+    fn test_builder_invalid_size() {
+        let result = PtyBuilder::new("true").size(0, 80).spawn();
+        assert!(matches!(
+            result,
+            Err(Error::InvalidSize { rows: 0, cols: 80 })
+        ));
+    }
+
+    #[test]
+    fn test_builder_cwd() {
+        let dir = std::env::temp_dir().canonicalize().unwrap();
+        let mut pty = PtyBuilder::new("pwd").cwd(&dir).spawn().unwrap();
+        assert!(read_all(&mut pty).contains(dir.to_str().unwrap()));
+    }
+
+    #[test]
+    fn test_builder_missing_cwd() {
+        let result = PtyBuilder::new("pwd").cwd("/no/such/directory").spawn();
+        assert!(matches!(result, Err(Error::SpawnCommand(_))));
+    }
+
+    #[test]
+    fn test_builder_command_not_found() {
+        let result = PtyBuilder::new("no-such-command-here").spawn();
+        assert!(
+            matches!(result, Err(Error::CommandNotFound(ref name)) if name == "no-such-command-here")
+        );
+    }
+
+    #[test]
+    fn test_builder_missing_path_command() {
+        let result = PtyBuilder::new("/no/such/command").spawn();
+        assert!(matches!(result, Err(Error::CommandNotFound(_))));
+    }
+
+    #[test]
+    fn test_builder_not_executable() {
+        let result = PtyBuilder::new("/etc/passwd").spawn();
+        assert!(matches!(result, Err(Error::CommandNotFound(_))));
+    }
+
+    #[test]
+    fn test_builder_searches_env_path() {
+        let result = PtyBuilder::new("sh")
+            .env("PATH", "/no/such/directory")
+            .spawn();
+        assert!(matches!(result, Err(Error::CommandNotFound(_))));
+
+        let result = PtyBuilder::new("sh").env_clear().spawn();
+        assert!(matches!(result, Err(Error::CommandNotFound(_))));
+
+        let result = PtyBuilder::new("sh")
+            .env_clear()
+            .env("PATH", "/bin:/usr/bin")
+            .spawn();
+        assert!(result.is_ok());
+    }
+
+    /// Create an empty directory for a test, with a script at `script`
+    #[cfg(unix)]
+    fn script_dir(name: &str, script: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir()
+            .canonicalize()
+            .unwrap()
+            .join(format!("dioxus-terminal-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join(script);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "#!/bin/sh\necho from-script\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        dir
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_builder_relative_command_uses_cwd() {
+        let dir = script_dir("relative", "hello.sh");
+        let mut pty = PtyBuilder::new("./hello.sh").cwd(&dir).spawn().unwrap();
+        assert!(read_all(&mut pty).contains("from-script"));
+
+        // Not found next to the cwd of this process
+        let result = PtyBuilder::new("./hello.sh").cwd("/").spawn();
+        assert!(matches!(result, Err(Error::CommandNotFound(_))));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_builder_relative_path_entry_uses_cwd() {
+        let dir = script_dir("path-entry", "bin/hello-tool");
+        let mut pty = PtyBuilder::new("hello-tool")
+            .cwd(&dir)
+            .env("PATH", "bin")
+            .spawn()
+            .unwrap();
+        assert!(read_all(&mut pty).contains("from-script"));
+
+        let result = PtyBuilder::new("hello-tool")
+            .cwd("/")
+            .env("PATH", "bin")
+            .spawn();
+        assert!(matches!(result, Err(Error::CommandNotFound(_))));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_builder_relative_command_uses_home() {
+        let dir = script_dir("home", "hello.sh");
+        let mut pty = PtyBuilder::new("./hello.sh")
+            .env("HOME", dir.to_str().unwrap())
+            .spawn()
+            .unwrap();
+        assert!(read_all(&mut pty).contains("from-script"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_builder_default_term() {
+        let mut pty = PtyBuilder::new("sh")
+            .args(["-c", "echo \"$TERM $COLORTERM\""])
+            .spawn()
+            .unwrap();
+        assert!(read_all(&mut pty).contains("xterm-256color truecolor"));
+    }
+
+    #[test]
+    fn test_builder_env() {
+        let mut pty = PtyBuilder::new("sh")
+            .args(["-c", "echo \"$TERM|$GREETING|${COLORTERM-unset}\""])
+            .term("vt100")
+            .env("GREETING", "hello")
+            .env_remove("COLORTERM")
+            .spawn()
+            .unwrap();
+        assert!(read_all(&mut pty).contains("vt100|hello|unset"));
+    }
+
+    #[test]
+    fn test_builder_env_clear() {
+        let mut pty = PtyBuilder::new("/usr/bin/env")
+            .env_clear()
+            .env("ONLY", "this")
+            .spawn()
+            .unwrap();
+        let output = read_all(&mut pty);
+        let vars: Vec<&str> = output.lines().map(str::trim).collect();
+        assert!(vars.contains(&"ONLY=this"));
+        assert!(vars.contains(&"TERM=xterm-256color"));
+        assert!(vars.contains(&"COLORTERM=truecolor"));
+        assert!(!vars.iter().any(|var| var.starts_with("PATH=")));
+    }
+
+    #[test]
+    fn test_wait_success() {
+        let mut pty = Pty::spawn("true", &[], 24, 80).unwrap();
+        assert!(pty.exit_status().is_none());
+        let status = pty.wait().unwrap();
+        assert!(status.success());
+        assert_eq!(pty.exit_status().map(ExitStatus::exit_code), Some(0));
+    }
+
+    #[test]
+    fn test_wait_exit_code() {
+        let mut pty = Pty::spawn("sh", &["-c", "exit 3"], 24, 80).unwrap();
+        let status = pty.wait().unwrap();
+        assert!(!status.success());
+        assert_eq!(status.exit_code(), 3);
+    }
+
+    #[test]
+    fn test_try_wait() {
+        let mut pty = Pty::spawn("sh", &["-c", "read _"], 24, 80).unwrap();
+        assert!(pty.try_wait().unwrap().is_none());
+
+        pty.write(b"\n").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let status = loop {
+            if let Some(status) = pty.try_wait().unwrap() {
+                break status;
+            }
+            assert!(Instant::now() < deadline, "process did not exit");
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        assert!(status.success());
+        assert!(pty.try_wait().unwrap().is_some());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_signal_interrupt() {
+        let mut pty = Pty::spawn("sleep", &["30"], 24, 80).unwrap();
+        pty.signal(PtySignal::Interrupt).unwrap();
+        let status = pty.wait().unwrap();
+        assert!(!status.success());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_signal_reaches_process_group() {
+        // The shell survives TERM and waits for its background job
+        let script = "trap 'echo term' TERM; sleep 30 & wait; wait";
+        let mut pty = Pty::spawn("sh", &["-c", script], 24, 80).unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+        pty.signal(PtySignal::Terminate).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while pty.try_wait().unwrap().is_none() {
+            assert!(Instant::now() < deadline, "background process survived");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_signal_reaches_foreground_job() {
+        // With job control the shell runs `sleep` in its own foreground
+        // group; the trap keeps the shell going once the job is interrupted
+        let script = "trap 'echo trapped' INT; set -m; sleep 30; echo status=$?";
+        let mut pty = Pty::spawn("sh", &["-c", script], 24, 80).unwrap();
+        let pid = pty.pid().unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while pty
+            .master
+            .process_group_leader()
+            .is_none_or(|group| group == pid)
+        {
+            assert!(
+                Instant::now() < deadline,
+                "job never reached the foreground"
+            );
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        pty.signal(PtySignal::Interrupt).unwrap();
+        assert!(read_until(&mut pty, "status=").contains("status=130"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_drop_reaps_process() {
+        let pty = Pty::spawn("sleep", &["30"], 24, 80).unwrap();
+        let pid = pty.pid().unwrap();
+        drop(pty);
+
+        // The process is gone entirely once it has been waited for
+        let deadline = Instant::now() + Duration::from_secs(5);
+        // SAFETY: kill with signal 0 only checks that the process exists
+        while unsafe { libc::kill(pid, 0) } == 0 {
+            assert!(Instant::now() < deadline, "process was never reaped");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_drop_kills_process_ignoring_hangup() {
+        let mut pty =
+            Pty::spawn("sh", &["-c", "trap '' HUP; echo ready; sleep 30"], 24, 80).unwrap();
+        read_until(&mut pty, "ready");
+        let pid = pty.pid().unwrap();
+        drop(pty);
+
+        let deadline = Instant::now() + HANGUP_TIMEOUT + Duration::from_secs(5);
+        // SAFETY: kill with signal 0 only checks that the process exists
+        while unsafe { libc::kill(pid, 0) } == 0 {
+            assert!(Instant::now() < deadline, "process survived the drop");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_signal_after_exit() {
+        let mut pty = Pty::spawn("true", &[], 24, 80).unwrap();
+        pty.wait().unwrap();
+        assert!(pty.signal(PtySignal::Terminate).is_ok());
+        assert!(pty.kill().is_ok());
+    }
+
+    #[test]
+    fn test_kill() {
+        let mut pty = Pty::spawn("sleep", &["30"], 24, 80).unwrap();
+        pty.kill().unwrap();
+        let status = pty.wait().unwrap();
+        assert!(!status.success());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_terminate_graceful() {
+        let mut pty = Pty::spawn("sleep", &["30"], 24, 80).unwrap();
+        let start = Instant::now();
+        let status = pty.terminate(Duration::from_secs(5)).unwrap();
+        assert!(!status.success());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_terminate_escalates_to_kill() {
+        let mut pty =
+            Pty::spawn("sh", &["-c", "trap '' TERM; echo ready; sleep 30"], 24, 80).unwrap();
+        read_until(&mut pty, "ready");
+
+        let status = pty.terminate(Duration::from_millis(200)).unwrap();
+        assert!(!status.success());
+    }
+
     #[test]
     fn test_resize_valid() {
         let mut pty = Pty::spawn("echo", &["test"], 24, 80).unwrap();
@@ -190,6 +1034,22 @@ mod tests {
         assert_eq!(pty.size(), (40, 120));
     }
 
+    #[test]
+    fn test_resize_updates_kernel_size() {
+        let mut pty = Pty::spawn("sleep", &["1"], 24, 80).unwrap();
+        pty.resize(40, 120).unwrap();
+        let size = pty.master.get_size().unwrap();
+        assert_eq!((size.rows, size.cols), (40, 120));
+    }
+
+    #[test]
+    fn test_resize_reaches_child() {
+        let mut pty = Pty::spawn("sh", &["-c", "read _; stty size"], 24, 80).unwrap();
+        pty.resize(30, 100).unwrap();
+        pty.write(b"\n").unwrap();
+        read_until(&mut pty, "30 100");
+    }
+
     #[test]
     fn test_resize_invalid() {
         let mut pty = Pty::spawn("echo", &["test"], 24, 80).unwrap();
@@ -7,21 +7,35 @@ use vte::ansi::Rgb;
 pub struct Cell {
     /// Character displayed in this cell
     pub c: char,
+    /// Combining characters drawn over `c`, such as the accent of a
+    /// decomposed "ü"
+    pub zerowidth: Vec<char>,
     /// Foreground color
-    pub fg: Color,
+    pub fg: CellColor,
     /// Background color
-    pub bg: Color,
+    pub bg: CellColor,
     /// Text style
     pub style: Style,
+    /// Display width in columns (2 for wide characters, 0 for the spacer
+    /// cell that follows a wide character)
+    pub width: u8,
+    /// Whether the line soft-wraps onto the next row after this cell
+    pub wrapped: bool,
+    /// Underline color, or `None` to use the foreground color
+    pub underline_color: Option<CellColor>,
 }
 
 impl Default for Cell {
     fn default() -> Self {
         Self {
             c: ' ',
-            fg: Color::default_fg(),
-            bg: Color::default_bg(),
+            zerowidth: Vec::new(),
+            fg: CellColor::Default,
+            bg: CellColor::Default,
             style: Style::default(),
+            width: 1,
+            wrapped: false,
+            underline_color: None,
         }
     }
 }
@@ -38,19 +52,55 @@ impl Cell {
 
     /// Create a cell with character and colors
     #[must_use]
-    pub fn with_colors(c: char, fg: Color, bg: Color) -> Self {
+    pub fn with_colors(c: char, fg: impl Into<CellColor>, bg: impl Into<CellColor>) -> Self {
         Self {
             c,
-            fg,
-            bg,
+            zerowidth: Vec::new(),
+            fg: fg.into(),
+            bg: bg.into(),
             style: Style::default(),
+            width: 1,
+            wrapped: false,
+            underline_color: None,
         }
     }
 
+    /// Get the text drawn in this cell, including combining characters
+    #[must_use]
+    pub fn text(&self) -> String {
+        std::iter::once(self.c)
+            .chain(self.zerowidth.iter().copied())
+            .collect()
+    }
+
     /// Check if this cell is empty (whitespace with default colors)
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.c.is_whitespace() && self.fg == Color::default_fg() && self.bg == Color::default_bg()
+        self.c.is_whitespace() && self.fg == CellColor::Default && self.bg == CellColor::Default
+    }
+
+    /// Check if this cell is the placeholder following a wide character
+    #[must_use]
+    pub const fn is_wide_spacer(&self) -> bool {
+        self.width == 0
+    }
+}
+
+/// Color of a cell, resolved against the theme when rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CellColor {
+    /// The default foreground or background
+    #[default]
+    Default,
+    /// An entry of the 256-color palette
+    Indexed(u8),
+    /// A 24-bit color
+    Rgb(Color),
+}
+
+impl From<Color> for CellColor {
+    fn from(color: Color) -> Self {
+        Self::Rgb(color)
     }
 }
 
@@ -81,6 +131,46 @@ impl Color {
         Self::new(0, 0, 0)
     }
 
+    /// Get a color of the standard 256-color palette
+    ///
+    /// Indexes 16-231 form a 6x6x6 color cube and 232-255 a grayscale ramp.
+    #[must_use]
+    pub const fn indexed(index: u8) -> Self {
+        match index {
+            // Standard colors (0-15)
+            0 => Self::new(0, 0, 0),
+            1 => Self::new(205, 49, 49),
+            2 => Self::new(13, 188, 121),
+            3 => Self::new(229, 229, 16),
+            4 => Self::new(36, 114, 200),
+            5 => Self::new(188, 63, 188),
+            6 => Self::new(17, 168, 205),
+            7 => Self::new(229, 229, 229),
+            8 => Self::new(102, 102, 102),
+            9 => Self::new(241, 76, 76),
+            10 => Self::new(35, 209, 139),
+            11 => Self::new(245, 245, 67),
+            12 => Self::new(59, 142, 234),
+            13 => Self::new(214, 112, 214),
+            14 => Self::new(41, 184, 219),
+            15 => Self::new(255, 255, 255),
+            // 216-color cube (16-231)
+            16..=231 => {
+                let n = index - 16;
+                Self::new(
+                    cube_level(n / 36),
+                    cube_level((n / 6) % 6),
+                    cube_level(n % 6),
+                )
+            }
+            // Grayscale (232-255)
+            232..=255 => {
+                let gray = 8 + (index - 232) * 10;
+                Self::new(gray, gray, gray)
+            }
+        }
+    }
+
     /// Convert to CSS `rgb()` string
     #[must_use]
     pub fn to_css(&self) -> String {
@@ -92,6 +182,30 @@ impl Color {
     pub fn to_hex(&self) -> String {
         format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
     }
+
+    /// Parse a hex color: `#rrggbb`, `#rgb`, `0xrrggbb` or bare `rrggbb`
+    #[must_use]
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let digits = hex
+            .strip_prefix('#')
+            .or_else(|| hex.strip_prefix("0x"))
+            .or_else(|| hex.strip_prefix("0X"))
+            .unwrap_or(hex);
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |range: std::ops::Range<usize>| u8::from_str_radix(&digits[range], 16).ok();
+        match digits.len() {
+            6 => Some(Self::new(channel(0..2)?, channel(2..4)?, channel(4..6)?)),
+            // #rgb repeats each digit
+            3 => Some(Self::new(
+                channel(0..1)? * 17,
+                channel(1..2)? * 17,
+                channel(2..3)? * 17,
+            )),
+            _ => None,
+        }
+    }
 }
 
 impl From<Rgb> for Color {
@@ -100,6 +214,41 @@ impl From<Rgb> for Color {
     }
 }
 
+/// Intensity of a 6x6x6 color cube component
+const fn cube_level(level: u8) -> u8 {
+    if level == 0 { 0 } else { 55 + level * 40 }
+}
+
+/// Shape of an underline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnderlineStyle {
+    /// SGR 4 or 4:1
+    #[default]
+    Single,
+    /// SGR 21 or 4:2
+    Double,
+    /// SGR 4:3
+    Curly,
+    /// SGR 4:4
+    Dotted,
+    /// SGR 4:5
+    Dashed,
+}
+
+impl UnderlineStyle {
+    /// Get the CSS `text-decoration-style` value
+    #[must_use]
+    pub const fn to_css(self) -> &'static str {
+        match self {
+            Self::Single => "solid",
+            Self::Double => "double",
+            Self::Curly => "wavy",
+            Self::Dotted => "dotted",
+            Self::Dashed => "dashed",
+        }
+    }
+}
+
 /// Text style flags
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[allow(clippy::struct_excessive_bools)]
@@ -107,9 +256,14 @@ pub struct Style {
     pub bold: bool,
     pub italic: bool,
     pub underline: bool,
+    /// Shape of the underline, when `underline` is set
+    pub underline_style: UnderlineStyle,
     pub strikethrough: bool,
+    pub overline: bool,
     pub dim: bool,
     pub inverse: bool,
+    pub hidden: bool,
+    pub blink: bool,
 }
 
 impl Style {
@@ -120,19 +274,34 @@ impl Style {
             bold: false,
             italic: false,
             underline: false,
+            underline_style: UnderlineStyle::Single,
             strikethrough: false,
+            overline: false,
             dim: false,
             inverse: false,
+            hidden: false,
+            blink: false,
         }
     }
 
     /// Check if any style is applied
     #[must_use]
     pub const fn has_any(&self) -> bool {
-        self.bold || self.italic || self.underline || self.strikethrough || self.dim || self.inverse
+        self.bold
+            || self.italic
+            || self.underline
+            || self.strikethrough
+            || self.overline
+            || self.dim
+            || self.inverse
+            || self.hidden
+            || self.blink
     }
 
     /// Generate CSS classes for this style
+    ///
+    /// The underline's shape and color are in [`Style::to_underline_css`].
+    /// `inverse` and `hidden` change colors, which the renderer applies.
     #[must_use]
     pub fn to_css_classes(&self) -> String {
         let mut classes = Vec::new();
@@ -148,11 +317,45 @@ impl Style {
         if self.strikethrough {
             classes.push("line-through");
         }
+        if self.overline {
+            classes.push("overline");
+        }
         if self.dim {
             classes.push("opacity-50");
         }
+        if self.blink {
+            classes.push("animate-pulse");
+        }
         classes.join(" ")
     }
+
+    /// Generate inline CSS declarations for the underline
+    #[must_use]
+    pub fn to_underline_css(&self) -> String {
+        match self.underline_style {
+            _ if !self.underline => String::new(),
+            UnderlineStyle::Single => "text-decoration-line: underline;".to_string(),
+            style => format!(
+                "text-decoration-line: underline; text-decoration-style: {};",
+                style.to_css()
+            ),
+        }
+    }
+
+    /// Generate inline CSS declarations for overline and strikethrough
+    ///
+    /// These go on an element inside the underlined one, so the
+    /// underline's shape and color do not carry over to them.
+    #[must_use]
+    pub fn to_line_css(&self) -> String {
+        let lines = match (self.overline, self.strikethrough) {
+            (false, false) => return String::new(),
+            (true, false) => "overline",
+            (false, true) => "line-through",
+            (true, true) => "overline line-through",
+        };
+        format!("text-decoration-line: {lines};")
+    }
 }
 
 /// Terminal grid containing all cells
@@ -233,6 +436,9 @@ impl Grid {
 
     /// Resize the grid, preserving content where possible
     ///
+    /// Rows are truncated or padded; use [`crate::Emulator::resize`] to
+    /// reflow wrapped lines.
+    ///
     /// # Panics
     ///
     /// Panics if `new_rows` or `new_cols` is 0.
@@ -272,14 +478,58 @@ mod tests {
         assert!(!cell.is_empty());
     }
 
+    #[test]
+    fn test_cell_text() {
+        let mut cell = Cell::new('u');
+        assert_eq!(cell.text(), "u");
+        cell.zerowidth.push('\u{0308}');
+        assert_eq!(cell.text(), "u\u{0308}");
+    }
+
     #[test]
     fn test_cell_with_colors() {
         let fg = Color::new(255, 0, 0);
         let bg = Color::new(0, 0, 255);
         let cell = Cell::with_colors('X', fg, bg);
         assert_eq!(cell.c, 'X');
-        assert_eq!(cell.fg, fg);
-        assert_eq!(cell.bg, bg);
+        assert_eq!(cell.fg, CellColor::Rgb(fg));
+        assert_eq!(cell.bg, CellColor::Rgb(bg));
+        assert!(!cell.is_empty());
+    }
+
+    #[test]
+    fn test_cell_color_default() {
+        assert_eq!(CellColor::default(), CellColor::Default);
+        assert_eq!(Cell::default().fg, CellColor::Default);
+        let cell = Cell::with_colors(' ', CellColor::Default, CellColor::Indexed(4));
+        assert!(!cell.is_empty());
+    }
+
+    #[test]
+    fn test_color_indexed() {
+        assert_eq!(Color::indexed(1), Color::new(205, 49, 49));
+        assert_eq!(Color::indexed(16), Color::new(0, 0, 0));
+        assert_eq!(Color::indexed(196), Color::new(255, 0, 0));
+        assert_eq!(Color::indexed(231), Color::new(255, 255, 255));
+        assert_eq!(Color::indexed(232), Color::new(8, 8, 8));
+        assert_eq!(Color::indexed(255), Color::new(238, 238, 238));
+    }
+
+    #[test]
+    fn test_cell_width() {
+        assert_eq!(Cell::default().width, 1);
+        assert!(!Cell::new('A').is_wide_spacer());
+        let spacer = Cell {
+            width: 0,
+            ..Cell::default()
+        };
+        assert!(spacer.is_wide_spacer());
+    }
+
+    #[test]
+    fn test_cell_wrapped() {
+        assert!(!Cell::default().wrapped);
+        assert!(!Cell::with_colors('A', Color::default_fg(), Color::default_bg()).wrapped);
     }
 
     #[test]
@@ -302,6 +552,20 @@ mod tests {
         assert_eq!(c.to_hex(), "#ff8000");
     }
 
+    #[test]
+    fn test_color_from_hex() {
+        let c = Color::new(255, 128, 0);
+        assert_eq!(Color::from_hex("#ff8000"), Some(c));
+        assert_eq!(Color::from_hex("0xFF8000"), Some(c));
+        assert_eq!(Color::from_hex("ff8000"), Some(c));
+        assert_eq!(Color::from_hex("#f80"), Some(Color::new(255, 136, 0)));
+        assert_eq!(Color::from_hex(&c.to_hex()), Some(c));
+        assert_eq!(Color::from_hex("#ff80"), None);
+        assert_eq!(Color::from_hex("#gg8000"), None);
+        assert_eq!(Color::from_hex("#ff80é"), None);
+        assert_eq!(Color::from_hex(""), None);
+    }
+
     #[test]
     fn test_style_default() {
         let s = Style::default();
@@ -323,16 +587,82 @@ mod tests {
         let s = Style {
             bold: true,
             italic: true,
-            underline: false,
-            strikethrough: false,
-            dim: false,
-            inverse: false,
+            ..Style::new()
         };
         let classes = s.to_css_classes();
         assert!(classes.contains("font-bold"));
         assert!(classes.contains("italic"));
     }
 
+    #[test]
+    fn test_style_to_css_classes_decorations() {
+        let s = Style {
+            underline: true,
+            strikethrough: true,
+            overline: true,
+            blink: true,
+            ..Style::new()
+        };
+        assert_eq!(
+            s.to_css_classes(),
+            "underline line-through overline animate-pulse"
+        );
+    }
+
+    #[test]
+    fn test_style_to_css() {
+        assert_eq!(Style::new().to_underline_css(), "");
+        assert_eq!(Style::new().to_line_css(), "");
+
+        let s = Style {
+            underline: true,
+            strikethrough: true,
+            overline: true,
+            ..Style::new()
+        };
+        assert_eq!(s.to_underline_css(), "text-decoration-line: underline;");
+        assert_eq!(
+            s.to_line_css(),
+            "text-decoration-line: overline line-through;"
+        );
+
+        let s = Style {
+            underline: true,
+            underline_style: UnderlineStyle::Curly,
+            strikethrough: true,
+            ..Style::new()
+        };
+        assert_eq!(
+            s.to_underline_css(),
+            "text-decoration-line: underline; text-decoration-style: wavy;"
+        );
+        assert_eq!(s.to_line_css(), "text-decoration-line: line-through;");
+    }
+
+    #[test]
+    fn test_style_has_any_extended() {
+        for s in [
+            Style {
+                hidden: true,
+                ..Style::new()
+            },
+            Style {
+                blink: true,
+                ..Style::new()
+            },
+            Style {
+                overline: true,
+                ..Style::new()
+            },
+            Style {
+                inverse: true,
+                ..Style::new()
+            },
+        ] {
+            assert!(s.has_any());
+        }
+    }
+
     #[test]
     fn test_grid_new() {
         let grid = Grid::new(24, 80);
@@ -1,6 +1,6 @@
 //! Terminal color themes
 
-use crate::term::Color;
+use crate::term::{CellColor, Color};
 
 /// Terminal color theme
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -13,10 +13,36 @@ pub struct Theme {
     pub cursor: Option<Color>,
     /// Selection background color
     pub selection: Option<Color>,
+    /// ANSI colors 0-15: the eight normal colors followed by their bright variants
+    pub palette: [Color; 16],
+    /// Color for bold text with the default foreground (defaults to foreground)
+    pub bold: Option<Color>,
+    /// Color for dim text with the default foreground, instead of fading it
+    pub dim: Option<Color>,
 }
 
 impl Theme {
-    /// Create a custom theme
+    /// ANSI palette used by [`Theme::new`] (VS Code's terminal colors)
+    pub const DEFAULT_PALETTE: [Color; 16] = [
+        Color::indexed(0),
+        Color::indexed(1),
+        Color::indexed(2),
+        Color::indexed(3),
+        Color::indexed(4),
+        Color::indexed(5),
+        Color::indexed(6),
+        Color::indexed(7),
+        Color::indexed(8),
+        Color::indexed(9),
+        Color::indexed(10),
+        Color::indexed(11),
+        Color::indexed(12),
+        Color::indexed(13),
+        Color::indexed(14),
+        Color::indexed(15),
+    ];
+
+    /// Create a custom theme with the default ANSI palette
     #[must_use]
     pub const fn new(background: Color, foreground: Color) -> Self {
         Self {
@@ -24,6 +50,41 @@ impl Theme {
             foreground,
             cursor: None,
             selection: None,
+            palette: Self::DEFAULT_PALETTE,
+            bold: None,
+            dim: None,
+        }
+    }
+
+    /// Replace the ANSI palette (colors 0-15)
+    #[must_use]
+    pub const fn with_palette(mut self, palette: [Color; 16]) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Check whether the background is dark, by its perceived brightness
+    #[must_use]
+    pub fn is_dark(&self) -> bool {
+        let Color { r, g, b } = self.background;
+        let brightness = u32::from(r) * 299 + u32::from(g) * 587 + u32::from(b) * 114;
+        brightness < 128_000
+    }
+
+    /// Resolve a cell color, using `default` for [`CellColor::Default`]
+    ///
+    /// Indexes 0-15 come from the theme's palette, the rest from the
+    /// standard 256-color palette.
+    #[must_use]
+    pub fn resolve(&self, color: CellColor, default: Color) -> Color {
+        match color {
+            CellColor::Default => default,
+            CellColor::Indexed(index) => self
+                .palette
+                .get(usize::from(index))
+                .copied()
+                .unwrap_or_else(|| Color::indexed(index)),
+            CellColor::Rgb(color) => color,
         }
     }
 
@@ -48,80 +109,1049 @@ impl Theme {
     /// Nord theme - polar night background
     #[must_use]
     pub const fn nord() -> Self {
-        Self::new(Color::new(46, 52, 64), Color::new(216, 222, 233))
+        Self::new(Color::new(46, 52, 64), Color::new(216, 222, 233)).with_palette([
+            Color::new(59, 66, 82),
+            Color::new(191, 97, 106),
+            Color::new(163, 190, 140),
+            Color::new(235, 203, 139),
+            Color::new(129, 161, 193),
+            Color::new(180, 142, 173),
+            Color::new(136, 192, 208),
+            Color::new(229, 233, 240),
+            Color::new(76, 86, 106),
+            Color::new(191, 97, 106),
+            Color::new(163, 190, 140),
+            Color::new(235, 203, 139),
+            Color::new(129, 161, 193),
+            Color::new(180, 142, 173),
+            Color::new(143, 188, 187),
+            Color::new(236, 239, 244),
+        ])
     }
 
     /// Dracula theme
     #[must_use]
     pub const fn dracula() -> Self {
-        Self::new(Color::new(40, 42, 54), Color::new(248, 248, 242))
+        Self::new(Color::new(40, 42, 54), Color::new(248, 248, 242)).with_palette([
+            Color::new(33, 34, 44),
+            Color::new(255, 85, 85),
+            Color::new(80, 250, 123),
+            Color::new(241, 250, 140),
+            Color::new(189, 147, 249),
+            Color::new(255, 121, 198),
+            Color::new(139, 233, 253),
+            Color::new(248, 248, 242),
+            Color::new(98, 114, 164),
+            Color::new(255, 110, 110),
+            Color::new(105, 255, 148),
+            Color::new(255, 255, 165),
+            Color::new(214, 172, 255),
+            Color::new(255, 146, 223),
+            Color::new(164, 255, 255),
+            Color::new(255, 255, 255),
+        ])
     }
 
     /// Monokai theme
     #[must_use]
     pub const fn monokai() -> Self {
-        Self::new(Color::new(39, 40, 34), Color::new(248, 248, 242))
+        Self::new(Color::new(39, 40, 34), Color::new(248, 248, 242)).with_palette([
+            Color::new(39, 40, 34),
+            Color::new(249, 38, 114),
+            Color::new(166, 226, 46),
+            Color::new(244, 191, 117),
+            Color::new(102, 217, 239),
+            Color::new(174, 129, 255),
+            Color::new(161, 239, 228),
+            Color::new(248, 248, 242),
+            Color::new(117, 113, 94),
+            Color::new(249, 38, 114),
+            Color::new(166, 226, 46),
+            Color::new(244, 191, 117),
+            Color::new(102, 217, 239),
+            Color::new(174, 129, 255),
+            Color::new(161, 239, 228),
+            Color::new(249, 248, 245),
+        ])
     }
 
     /// Solarized Dark theme
     #[must_use]
     pub const fn solarized_dark() -> Self {
-        Self::new(Color::new(0, 43, 54), Color::new(131, 148, 150))
+        Self::new(Color::new(0, 43, 54), Color::new(131, 148, 150)).with_palette(SOLARIZED_PALETTE)
     }
 
     /// Solarized Light theme
     #[must_use]
     pub const fn solarized_light() -> Self {
         Self::new(Color::new(253, 246, 227), Color::new(101, 123, 131))
+            .with_palette(SOLARIZED_PALETTE)
     }
 
-    /// Light theme - white background, dark text
+    /// Light theme - white background, dark text (VS Code's light terminal colors)
     #[must_use]
     pub const fn light() -> Self {
-        Self::new(Color::new(255, 255, 255), Color::new(30, 30, 30))
+        Self::new(Color::new(255, 255, 255), Color::new(30, 30, 30)).with_palette([
+            Color::new(0, 0, 0),
+            Color::new(205, 49, 49),
+            Color::new(16, 124, 16),
+            Color::new(148, 152, 0),
+            Color::new(4, 81, 165),
+            Color::new(188, 5, 188),
+            Color::new(5, 152, 188),
+            Color::new(85, 85, 85),
+            Color::new(102, 102, 102),
+            Color::new(205, 49, 49),
+            Color::new(20, 206, 20),
+            Color::new(181, 186, 0),
+            Color::new(4, 81, 165),
+            Color::new(188, 5, 188),
+            Color::new(5, 152, 188),
+            Color::new(165, 165, 165),
+        ])
     }
 
     /// GitHub Dark theme
     #[must_use]
     pub const fn github_dark() -> Self {
-        Self::new(Color::new(13, 17, 23), Color::new(201, 209, 217))
+        Self::new(Color::new(13, 17, 23), Color::new(201, 209, 217)).with_palette([
+            Color::new(72, 79, 88),
+            Color::new(255, 123, 114),
+            Color::new(63, 185, 80),
+            Color::new(210, 153, 34),
+            Color::new(88, 166, 255),
+            Color::new(188, 140, 255),
+            Color::new(57, 197, 207),
+            Color::new(177, 186, 196),
+            Color::new(110, 118, 129),
+            Color::new(255, 161, 152),
+            Color::new(86, 211, 100),
+            Color::new(227, 179, 65),
+            Color::new(121, 192, 255),
+            Color::new(210, 168, 255),
+            Color::new(86, 212, 221),
+            Color::new(240, 246, 252),
+        ])
     }
 
     /// Tokyo Night theme
     #[must_use]
     pub const fn tokyo_night() -> Self {
-        Self::new(Color::new(26, 27, 38), Color::new(169, 177, 214))
+        Self::new(Color::new(26, 27, 38), Color::new(169, 177, 214)).with_palette([
+            Color::new(21, 22, 30),
+            Color::new(247, 118, 142),
+            Color::new(158, 206, 106),
+            Color::new(224, 175, 104),
+            Color::new(122, 162, 247),
+            Color::new(187, 154, 247),
+            Color::new(125, 207, 255),
+            Color::new(169, 177, 214),
+            Color::new(65, 72, 104),
+            Color::new(247, 118, 142),
+            Color::new(158, 206, 106),
+            Color::new(224, 175, 104),
+            Color::new(122, 162, 247),
+            Color::new(187, 154, 247),
+            Color::new(125, 207, 255),
+            Color::new(192, 202, 245),
+        ])
     }
 
     /// Catppuccin Mocha theme
     #[must_use]
     pub const fn catppuccin() -> Self {
-        Self::new(Color::new(30, 30, 46), Color::new(205, 214, 244))
+        Self::new(Color::new(30, 30, 46), Color::new(205, 214, 244)).with_palette([
+            Color::new(69, 71, 90),
+            Color::new(243, 139, 168),
+            Color::new(166, 227, 161),
+            Color::new(249, 226, 175),
+            Color::new(137, 180, 250),
+            Color::new(245, 194, 231),
+            Color::new(148, 226, 213),
+            Color::new(186, 194, 222),
+            Color::new(88, 91, 112),
+            Color::new(243, 139, 168),
+            Color::new(166, 227, 161),
+            Color::new(249, 226, 175),
+            Color::new(137, 180, 250),
+            Color::new(245, 194, 231),
+            Color::new(148, 226, 213),
+            Color::new(166, 173, 200),
+        ])
     }
 
     /// One Dark theme (Atom)
     #[must_use]
     pub const fn one_dark() -> Self {
-        Self::new(Color::new(40, 44, 52), Color::new(171, 178, 191))
+        Self::new(Color::new(40, 44, 52), Color::new(171, 178, 191)).with_palette([
+            Color::new(40, 44, 52),
+            Color::new(224, 108, 117),
+            Color::new(152, 195, 121),
+            Color::new(229, 192, 123),
+            Color::new(97, 175, 239),
+            Color::new(198, 120, 221),
+            Color::new(86, 182, 194),
+            Color::new(171, 178, 191),
+            Color::new(92, 99, 112),
+            Color::new(224, 108, 117),
+            Color::new(152, 195, 121),
+            Color::new(229, 192, 123),
+            Color::new(97, 175, 239),
+            Color::new(198, 120, 221),
+            Color::new(86, 182, 194),
+            Color::new(255, 255, 255),
+        ])
     }
 
     /// Gruvbox Dark theme
     #[must_use]
     pub const fn gruvbox() -> Self {
-        Self::new(Color::new(40, 40, 40), Color::new(235, 219, 178))
+        Self::new(Color::new(40, 40, 40), Color::new(235, 219, 178)).with_palette([
+            Color::new(40, 40, 40),
+            Color::new(204, 36, 29),
+            Color::new(152, 151, 26),
+            Color::new(215, 153, 33),
+            Color::new(69, 133, 136),
+            Color::new(177, 98, 134),
+            Color::new(104, 157, 106),
+            Color::new(168, 153, 132),
+            Color::new(146, 131, 116),
+            Color::new(251, 73, 52),
+            Color::new(184, 187, 38),
+            Color::new(250, 189, 47),
+            Color::new(131, 165, 152),
+            Color::new(211, 134, 155),
+            Color::new(142, 192, 124),
+            Color::new(235, 219, 178),
+        ])
     }
 }
 
+/// Solarized ANSI palette, shared by the dark and light variants
+const SOLARIZED_PALETTE: [Color; 16] = [
+    Color::new(7, 54, 66),     // base02
+    Color::new(220, 50, 47),   // red
+    Color::new(133, 153, 0),   // green
+    Color::new(181, 137, 0),   // yellow
+    Color::new(38, 139, 210),  // blue
+    Color::new(211, 54, 130),  // magenta
+    Color::new(42, 161, 152),  // cyan
+    Color::new(238, 232, 213), // base2
+    Color::new(0, 43, 54),     // base03
+    Color::new(203, 75, 22),   // orange
+    Color::new(88, 110, 117),  // base01
+    Color::new(101, 123, 131), // base00
+    Color::new(131, 148, 150), // base0
+    Color::new(108, 113, 196), // violet
+    Color::new(147, 161, 161), // base1
+    Color::new(253, 246, 227), // base3
+];
+
 impl Default for Theme {
     fn default() -> Self {
         Self::dark()
     }
 }
 
+/// How the terminal chooses its theme
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeMode {
+    /// Always use the same theme
+    Fixed(Theme),
+    /// Follow the desktop's light/dark preference (`prefers-color-scheme`)
+    Auto {
+        /// Theme used when the desktop prefers a light scheme
+        light: Theme,
+        /// Theme used when the desktop prefers a dark scheme
+        dark: Theme,
+    },
+}
+
+impl ThemeMode {
+    /// Get the theme for a desktop that prefers a dark (`true`) or light scheme
+    #[must_use]
+    pub const fn theme(&self, prefers_dark: bool) -> Theme {
+        match *self {
+            Self::Fixed(theme) => theme,
+            Self::Auto { light, dark } => {
+                if prefers_dark {
+                    dark
+                } else {
+                    light
+                }
+            }
+        }
+    }
+}
+
+impl Default for ThemeMode {
+    fn default() -> Self {
+        Self::Fixed(Theme::default())
+    }
+}
+
+impl From<Theme> for ThemeMode {
+    fn from(theme: Theme) -> Self {
+        Self::Fixed(theme)
+    }
+}
+
+/// Importers and exporters for other terminals' color scheme formats
+#[cfg(feature = "theme-import")]
+mod import {
+    use serde::Deserialize;
+
+    use super::Theme;
+    use crate::Result;
+    use crate::error::Error;
+    use crate::term::Color;
+
+    /// Build an [`Error::InvalidTheme`] naming the source format
+    fn invalid(format: &str, message: impl std::fmt::Display) -> Error {
+        Error::InvalidTheme(format!("{format}: {message}"))
+    }
+
+    fn parse_color(format: &str, value: &str) -> Result<Color> {
+        Color::from_hex(value.trim())
+            .ok_or_else(|| invalid(format, format!("invalid color `{value}`")))
+    }
+
+    fn parse_optional(format: &str, value: Option<&str>) -> Result<Option<Color>> {
+        value.map(|value| parse_color(format, value)).transpose()
+    }
+
+    /// Fill palette entries from `start` with the colors that are present
+    fn set_palette(
+        palette: &mut [Color; 16],
+        start: usize,
+        format: &str,
+        colors: [Option<&str>; 8],
+    ) -> Result<()> {
+        for (slot, value) in palette[start..].iter_mut().zip(colors) {
+            if let Some(value) = value {
+                *slot = parse_color(format, value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Alacritty config file; only the `colors` table is read
+    #[derive(Deserialize)]
+    struct AlacrittyConfig {
+        colors: AlacrittyColors,
+    }
+
+    #[derive(Deserialize)]
+    struct AlacrittyColors {
+        primary: AlacrittyPrimary,
+        #[serde(default)]
+        cursor: AlacrittyCursor,
+        #[serde(default)]
+        selection: AlacrittySelection,
+        #[serde(default)]
+        normal: AnsiColors,
+        #[serde(default)]
+        bright: AnsiColors,
+    }
+
+    #[derive(Deserialize)]
+    struct AlacrittyPrimary {
+        background: String,
+        foreground: String,
+        dim_foreground: Option<String>,
+        bright_foreground: Option<String>,
+    }
+
+    #[derive(Default, Deserialize)]
+    struct AlacrittyCursor {
+        cursor: Option<String>,
+    }
+
+    #[derive(Default, Deserialize)]
+    struct AlacrittySelection {
+        background: Option<String>,
+    }
+
+    /// The eight named colors of an Alacritty `normal`/`bright` table
+    #[derive(Default, Deserialize)]
+    struct AnsiColors {
+        black: Option<String>,
+        red: Option<String>,
+        green: Option<String>,
+        yellow: Option<String>,
+        blue: Option<String>,
+        magenta: Option<String>,
+        cyan: Option<String>,
+        white: Option<String>,
+    }
+
+    impl AnsiColors {
+        fn as_array(&self) -> [Option<&str>; 8] {
+            [
+                &self.black,
+                &self.red,
+                &self.green,
+                &self.yellow,
+                &self.blue,
+                &self.magenta,
+                &self.cyan,
+                &self.white,
+            ]
+            .map(Option::as_deref)
+        }
+    }
+
+    impl AlacrittyConfig {
+        fn into_theme(self, format: &str) -> Result<Theme> {
+            let colors = self.colors;
+            // `CellForeground`/`CellBackground` follow the cell, as our defaults do
+            let cell_color = |value: Option<String>| {
+                let value = value.filter(|value| !value.starts_with("Cell"));
+                parse_optional(format, value.as_deref())
+            };
+
+            let mut theme = Theme::new(
+                parse_color(format, &colors.primary.background)?,
+                parse_color(format, &colors.primary.foreground)?,
+            );
+            theme.cursor = cell_color(colors.cursor.cursor)?;
+            theme.selection = cell_color(colors.selection.background)?;
+            theme.bold = parse_optional(format, colors.primary.bright_foreground.as_deref())?;
+            theme.dim = parse_optional(format, colors.primary.dim_foreground.as_deref())?;
+            set_palette(&mut theme.palette, 0, format, colors.normal.as_array())?;
+            set_palette(&mut theme.palette, 8, format, colors.bright.as_array())?;
+            Ok(theme)
+        }
+    }
+
+    /// Windows Terminal `schemes` entry
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct WindowsTerminalScheme {
+        background: String,
+        foreground: String,
+        cursor_color: Option<String>,
+        selection_background: Option<String>,
+        black: Option<String>,
+        red: Option<String>,
+        green: Option<String>,
+        yellow: Option<String>,
+        blue: Option<String>,
+        purple: Option<String>,
+        cyan: Option<String>,
+        white: Option<String>,
+        bright_black: Option<String>,
+        bright_red: Option<String>,
+        bright_green: Option<String>,
+        bright_yellow: Option<String>,
+        bright_blue: Option<String>,
+        bright_purple: Option<String>,
+        bright_cyan: Option<String>,
+        bright_white: Option<String>,
+    }
+
+    /// base16 keys for ANSI colors 0-15
+    const BASE16_PALETTE: [&str; 16] = [
+        "base00", "base08", "base0b", "base0a", "base0d", "base0e", "base0c", "base05", //
+        "base03", "base08", "base0b", "base0a", "base0d", "base0e", "base0c", "base07",
+    ];
+
+    /// base24 keys for ANSI colors 0-15, with distinct bright colors
+    const BASE24_PALETTE: [&str; 16] = [
+        "base00", "base08", "base0b", "base0a", "base0d", "base0e", "base0c", "base06", //
+        "base02", "base12", "base14", "base13", "base16", "base17", "base15", "base07",
+    ];
+
+    /// iTerm2 color keys for ANSI colors 0-15
+    const ITERM_PALETTE: [&str; 16] = [
+        "Ansi 0 Color",
+        "Ansi 1 Color",
+        "Ansi 2 Color",
+        "Ansi 3 Color",
+        "Ansi 4 Color",
+        "Ansi 5 Color",
+        "Ansi 6 Color",
+        "Ansi 7 Color",
+        "Ansi 8 Color",
+        "Ansi 9 Color",
+        "Ansi 10 Color",
+        "Ansi 11 Color",
+        "Ansi 12 Color",
+        "Ansi 13 Color",
+        "Ansi 14 Color",
+        "Ansi 15 Color",
+    ];
+
+    /// Convert an iTerm2 color dictionary with 0.0-1.0 components
+    fn iterm_color(key: &str, value: &plist::Value) -> Result<Color> {
+        let dict = value
+            .as_dictionary()
+            .ok_or_else(|| invalid("iTerm2", format!("`{key}` is not a dictionary")))?;
+        let component = |name: &str| {
+            dict.get(name)
+                .and_then(plist::Value::as_real)
+                .map(unit_to_byte)
+                .ok_or_else(|| invalid("iTerm2", format!("`{key}` has no numeric `{name}`")))
+        };
+        Ok(Color::new(
+            component("Red Component")?,
+            component("Green Component")?,
+            component("Blue Component")?,
+        ))
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn unit_to_byte(value: f64) -> u8 {
+        (value.clamp(0.0, 1.0) * 255.0).round() as u8
+    }
+
+    /// Look up a base16 color; keys are case-insensitive
+    fn base16_color(scheme: &serde_norway::Mapping, key: &str) -> Result<Option<Color>> {
+        let value = scheme.iter().find_map(|(k, v)| {
+            k.as_str()
+                .filter(|k| k.eq_ignore_ascii_case(key))
+                .map(|_| v)
+        });
+        match value {
+            None => Ok(None),
+            Some(serde_norway::Value::String(value)) => parse_color("base16", value).map(Some),
+            // Unquoted all-digit values such as `181818` parse as numbers
+            Some(serde_norway::Value::Number(number)) => {
+                parse_color("base16", &format!("{:0>6}", number.to_string())).map(Some)
+            }
+            Some(_) => Err(invalid("base16", format!("`{key}` is not a color"))),
+        }
+    }
+
+    impl Theme {
+        /// Import an Alacritty color scheme in TOML format
+        ///
+        /// Reads the `[colors]` tables. `primary.bright_foreground` and
+        /// `primary.dim_foreground` become the bold and dim colors, and
+        /// missing `normal`/`bright` entries keep the default palette.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`Error::InvalidTheme`] if the input isn't TOML, lacks the
+        /// primary colors or contains a malformed color.
+        pub fn from_alacritty_toml(source: &str) -> Result<Self> {
+            let config: AlacrittyConfig =
+                toml::from_str(source).map_err(|e| invalid("Alacritty TOML", e))?;
+            config.into_theme("Alacritty TOML")
+        }
+
+        /// Import an Alacritty color scheme in the legacy YAML format
+        ///
+        /// Reads the same `colors` keys as [`Theme::from_alacritty_toml`].
+        ///
+        /// # Errors
+        ///
+        /// Returns [`Error::InvalidTheme`] if the input isn't YAML, lacks the
+        /// primary colors or contains a malformed color.
+        pub fn from_alacritty_yaml(source: &str) -> Result<Self> {
+            let config: AlacrittyConfig =
+                serde_norway::from_str(source).map_err(|e| invalid("Alacritty YAML", e))?;
+            config.into_theme("Alacritty YAML")
+        }
+
+        /// Import a kitty color scheme (`.conf`)
+        ///
+        /// Reads `foreground`, `background`, `cursor`, `selection_background`
+        /// and `color0`-`color15`; other settings are ignored.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`Error::InvalidTheme`] naming the line of a malformed
+        /// color, or if the foreground or background is missing.
+        pub fn from_kitty(source: &str) -> Result<Self> {
+            let mut background = None;
+            let mut foreground = None;
+            let mut cursor = None;
+            let mut selection = None;
+            let mut palette = Theme::DEFAULT_PALETTE;
+            for (number, line) in source.lines().enumerate() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let (key, value) = line
+                    .split_once(char::is_whitespace)
+                    .map_or((line, ""), |(key, value)| (key, value.trim()));
+                let color = || {
+                    Color::from_hex(value).ok_or_else(|| {
+                        invalid(
+                            "kitty",
+                            format!("line {}: invalid color `{value}`", number + 1),
+                        )
+                    })
+                };
+                let optional = || {
+                    if value == "none" {
+                        Ok(None)
+                    } else {
+                        color().map(Some)
+                    }
+                };
+                match key {
+                    "background" => background = Some(color()?),
+                    "foreground" => foreground = Some(color()?),
+                    "cursor" => cursor = optional()?,
+                    "selection_background" => selection = optional()?,
+                    _ => {
+                        let index = key
+                            .strip_prefix("color")
+                            .and_then(|n| n.parse::<usize>().ok());
+                        if let Some(slot) = index.and_then(|index| palette.get_mut(index)) {
+                            *slot = color()?;
+                        }
+                    }
+                }
+            }
+            let background = background.ok_or_else(|| invalid("kitty", "missing `background`"))?;
+            let foreground = foreground.ok_or_else(|| invalid("kitty", "missing `foreground`"))?;
+            let mut theme = Theme::new(background, foreground).with_palette(palette);
+            theme.cursor = cursor;
+            theme.selection = selection;
+            Ok(theme)
+        }
+
+        /// Import an iTerm2 color preset (`.itermcolors` property list)
+        ///
+        /// Components are taken as sRGB whatever the declared color space.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`Error::InvalidTheme`] if the input isn't a property list
+        /// dictionary, lacks the background or foreground, or contains a
+        /// malformed color.
+        pub fn from_iterm(source: &str) -> Result<Self> {
+            let value = plist::Value::from_reader(std::io::Cursor::new(source.as_bytes()))
+                .map_err(|e| invalid("iTerm2", e))?;
+            let dict = value
+                .as_dictionary()
+                .ok_or_else(|| invalid("iTerm2", "expected a dictionary"))?;
+            let color = |key: &str| {
+                dict.get(key)
+                    .map(|value| iterm_color(key, value))
+                    .transpose()
+            };
+            let required = |key: &str| {
+                color(key)?.ok_or_else(|| invalid("iTerm2", format!("missing `{key}`")))
+            };
+
+            let mut theme =
+                Theme::new(required("Background Color")?, required("Foreground Color")?);
+            theme.cursor = color("Cursor Color")?;
+            theme.selection = color("Selection Color")?;
+            theme.bold = color("Bold Color")?;
+            for (slot, key) in theme.palette.iter_mut().zip(ITERM_PALETTE) {
+                if let Some(value) = color(key)? {
+                    *slot = value;
+                }
+            }
+            Ok(theme)
+        }
+
+        /// Import a Windows Terminal color scheme (one `schemes` entry)
+        ///
+        /// # Errors
+        ///
+        /// Returns [`Error::InvalidTheme`] if the input isn't a JSON object,
+        /// lacks the background or foreground, or contains a malformed color.
+        pub fn from_windows_terminal(source: &str) -> Result<Self> {
+            const FORMAT: &str = "Windows Terminal";
+            let scheme: WindowsTerminalScheme =
+                serde_json::from_str(source).map_err(|e| invalid(FORMAT, e))?;
+            let mut theme = Theme::new(
+                parse_color(FORMAT, &scheme.background)?,
+                parse_color(FORMAT, &scheme.foreground)?,
+            );
+            theme.cursor = parse_optional(FORMAT, scheme.cursor_color.as_deref())?;
+            theme.selection = parse_optional(FORMAT, scheme.selection_background.as_deref())?;
+            let normal = [
+                &scheme.black,
+                &scheme.red,
+                &scheme.green,
+                &scheme.yellow,
+                &scheme.blue,
+                &scheme.purple,
+                &scheme.cyan,
+                &scheme.white,
+            ];
+            let bright = [
+                &scheme.bright_black,
+                &scheme.bright_red,
+                &scheme.bright_green,
+                &scheme.bright_yellow,
+                &scheme.bright_blue,
+                &scheme.bright_purple,
+                &scheme.bright_cyan,
+                &scheme.bright_white,
+            ];
+            set_palette(&mut theme.palette, 0, FORMAT, normal.map(Option::as_deref))?;
+            set_palette(&mut theme.palette, 8, FORMAT, bright.map(Option::as_deref))?;
+            Ok(theme)
+        }
+
+        /// Import a base16 or base24 scheme (YAML)
+        ///
+        /// Accepts both the classic flat layout and the newer one with the
+        /// colors under `palette`. Schemes defining `base10`-`base17` are
+        /// read as base24, which has its own bright colors.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`Error::InvalidTheme`] if the input isn't a YAML mapping,
+        /// a base color is missing or a color is malformed.
+        pub fn from_base16(source: &str) -> Result<Self> {
+            let value: serde_norway::Value =
+                serde_norway::from_str(source).map_err(|e| invalid("base16", e))?;
+            let scheme = value
+                .get("palette")
+                .unwrap_or(&value)
+                .as_mapping()
+                .ok_or_else(|| invalid("base16", "expected a mapping"))?;
+            let required = |key: &str| {
+                base16_color(scheme, key)?
+                    .ok_or_else(|| invalid("base16", format!("missing `{key}`")))
+            };
+
+            let keys = if base16_color(scheme, "base10")?.is_some() {
+                BASE24_PALETTE
+            } else {
+                BASE16_PALETTE
+            };
+            let mut theme = Theme::new(required("base00")?, required("base05")?);
+            theme.cursor = Some(theme.foreground);
+            theme.selection = Some(required("base02")?);
+            for (slot, key) in theme.palette.iter_mut().zip(keys) {
+                *slot = required(key)?;
+            }
+            Ok(theme)
+        }
+
+        /// Export as a kitty color scheme (`.conf`)
+        #[must_use]
+        pub fn to_kitty(&self) -> String {
+            let mut lines = vec![
+                format!("foreground {}", self.foreground.to_hex()),
+                format!("background {}", self.background.to_hex()),
+            ];
+            if let Some(cursor) = self.cursor {
+                lines.push(format!("cursor {}", cursor.to_hex()));
+            }
+            if let Some(selection) = self.selection {
+                lines.push(format!("selection_background {}", selection.to_hex()));
+            }
+            for (index, color) in self.palette.iter().enumerate() {
+                lines.push(format!("color{index} {}", color.to_hex()));
+            }
+            lines.push(String::new());
+            lines.join("\n")
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const ALACRITTY_TOML: &str = r##"
+[colors.primary]
+background = "#1d1f21"
+foreground = "0xc5c8c6"
+bright_foreground = "#ffffff"
+
+[colors.cursor]
+text = "CellBackground"
+cursor = "CellForeground"
+
+[colors.selection]
+background = "#373b41"
+
+[colors.normal]
+black = "#282a2e"
+red = "#a54242"
+
+[colors.bright]
+white = "#c5c8c6"
+"##;
+
+        #[test]
+        fn test_from_alacritty_toml() {
+            let theme = Theme::from_alacritty_toml(ALACRITTY_TOML).unwrap();
+            assert_eq!(theme.background, Color::new(0x1d, 0x1f, 0x21));
+            assert_eq!(theme.foreground, Color::new(0xc5, 0xc8, 0xc6));
+            assert_eq!(theme.bold, Some(Color::new(255, 255, 255)));
+            assert_eq!(theme.dim, None);
+            assert_eq!(theme.cursor, None);
+            assert_eq!(theme.selection, Some(Color::new(0x37, 0x3b, 0x41)));
+            assert_eq!(theme.palette[1], Color::new(0xa5, 0x42, 0x42));
+            assert_eq!(theme.palette[15], Color::new(0xc5, 0xc8, 0xc6));
+            // Unset entries keep the default palette
+            assert_eq!(theme.palette[2], Theme::DEFAULT_PALETTE[2]);
+        }
+
+        #[test]
+        fn test_from_alacritty_yaml() {
+            let source = "colors:\n  primary:\n    background: '#000000'\n    foreground: '#eeeeee'\n  normal:\n    red: '#ff0000'\n";
+            let theme = Theme::from_alacritty_yaml(source).unwrap();
+            assert_eq!(theme.foreground, Color::new(0xee, 0xee, 0xee));
+            assert_eq!(theme.palette[1], Color::new(255, 0, 0));
+        }
+
+        #[test]
+        fn test_from_alacritty_errors() {
+            let err = Theme::from_alacritty_toml("[colors.primary]\nbackground = \"#000000\"\n")
+                .unwrap_err();
+            assert!(err.to_string().contains("foreground"), "{err}");
+
+            let err = Theme::from_alacritty_toml(
+                "[colors.primary]\nbackground = \"#000000\"\nforeground = \"white\"\n",
+            )
+            .unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "invalid theme: Alacritty TOML: invalid color `white`"
+            );
+
+            assert!(Theme::from_alacritty_yaml("colors: [").is_err());
+        }
+
+        #[test]
+        fn test_from_kitty() {
+            let source = "# comment\n\nforeground   #dddddd\nbackground #000000\ncursor none\ncolor1 #ff0000\ncolor255 #ffffff\nfont_size 12\n";
+            let theme = Theme::from_kitty(source).unwrap();
+            assert_eq!(theme.foreground, Color::new(0xdd, 0xdd, 0xdd));
+            assert_eq!(theme.cursor, None);
+            assert_eq!(theme.palette[1], Color::new(255, 0, 0));
+        }
+
+        #[test]
+        fn test_from_kitty_errors() {
+            let err = Theme::from_kitty("background #000000\ncolor3 #12\n").unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "invalid theme: kitty: line 2: invalid color `#12`"
+            );
+            let err = Theme::from_kitty("background #000000\n").unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "invalid theme: kitty: missing `foreground`"
+            );
+        }
+
+        #[test]
+        fn test_kitty_round_trip() {
+            let theme = Theme::dracula();
+            assert_eq!(Theme::from_kitty(&theme.to_kitty()).unwrap(), theme);
+            assert!(theme.to_kitty().contains("color15 #ffffff\n"));
+        }
+
+        fn iterm_entry(key: &str, (r, g, b): (f64, f64, f64)) -> String {
+            format!(
+                "<key>{key}</key><dict><key>Color Space</key><string>sRGB</string>\
+                 <key>Red Component</key><real>{r}</real>\
+                 <key>Green Component</key><real>{g}</real>\
+                 <key>Blue Component</key><real>{b}</real></dict>"
+            )
+        }
+
+        fn iterm_plist(entries: &str) -> String {
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+                 <plist version=\"1.0\"><dict>{entries}</dict></plist>"
+            )
+        }
+
+        #[test]
+        fn test_from_iterm() {
+            let entries = [
+                iterm_entry("Background Color", (0.0, 0.0, 0.0)),
+                iterm_entry("Foreground Color", (1.0, 1.0, 1.0)),
+                iterm_entry("Ansi 1 Color", (1.0, 0.5, 0.0)),
+                iterm_entry("Bold Color", (0.9, 0.9, 0.9)),
+            ]
+            .concat();
+            let theme = Theme::from_iterm(&iterm_plist(&entries)).unwrap();
+            assert_eq!(theme.background, Color::new(0, 0, 0));
+            assert_eq!(theme.foreground, Color::new(255, 255, 255));
+            assert_eq!(theme.palette[1], Color::new(255, 128, 0));
+            assert_eq!(theme.bold, Some(Color::new(230, 230, 230)));
+            assert_eq!(theme.cursor, None);
+        }
+
+        #[test]
+        fn test_from_iterm_errors() {
+            let entries = iterm_entry("Background Color", (0.0, 0.0, 0.0));
+            let err = Theme::from_iterm(&iterm_plist(&entries)).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "invalid theme: iTerm2: missing `Foreground Color`"
+            );
+
+            let entries = "<key>Background Color</key><string>black</string>";
+            let err = Theme::from_iterm(&iterm_plist(entries)).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "invalid theme: iTerm2: `Background Color` is not a dictionary"
+            );
+
+            assert!(Theme::from_iterm("not a plist").is_err());
+        }
+
+        #[test]
+        fn test_from_windows_terminal() {
+            let source = r##"{
+                "name": "Campbell",
+                "background": "#0C0C0C",
+                "foreground": "#CCCCCC",
+                "cursorColor": "#FFFFFF",
+                "purple": "#881798",
+                "brightPurple": "#B4009E"
+            }"##;
+            let theme = Theme::from_windows_terminal(source).unwrap();
+            assert_eq!(theme.background, Color::new(12, 12, 12));
+            assert_eq!(theme.cursor, Some(Color::new(255, 255, 255)));
+            assert_eq!(theme.palette[5], Color::new(0x88, 0x17, 0x98));
+            assert_eq!(theme.palette[13], Color::new(0xb4, 0x00, 0x9e));
+
+            let err = Theme::from_windows_terminal(r##"{"background": "#000000"}"##).unwrap_err();
+            assert!(err.to_string().contains("foreground"), "{err}");
+        }
+
+        const BASE16: &str = "scheme: Test\nbase00: \"000000\"\nbase01: \"111111\"\nbase02: \"222222\"\nbase03: \"333333\"\nbase04: \"444444\"\nbase05: 555555\nbase06: \"666666\"\nbase07: \"777777\"\nbase08: \"880000\"\nbase09: \"990000\"\nbase0A: \"aa0000\"\nbase0B: \"bb0000\"\nbase0C: \"cc0000\"\nbase0D: \"dd0000\"\nbase0E: \"ee0000\"\nbase0F: \"ff0000\"\n";
+
+        #[test]
+        fn test_from_base16() {
+            let theme = Theme::from_base16(BASE16).unwrap();
+            assert_eq!(theme.background, Color::new(0, 0, 0));
+            assert_eq!(theme.foreground, Color::new(0x55, 0x55, 0x55));
+            assert_eq!(theme.selection, Some(Color::new(0x22, 0x22, 0x22)));
+            assert_eq!(theme.palette[1], Color::new(0x88, 0, 0));
+            assert_eq!(theme.palette[9], Color::new(0x88, 0, 0));
+            assert_eq!(theme.palette[8], Color::new(0x33, 0x33, 0x33));
+            assert_eq!(theme.palette[15], Color::new(0x77, 0x77, 0x77));
+        }
+
+        #[test]
+        fn test_from_base16_palette_layout() {
+            let nested = BASE16
+                .replace("scheme: Test\n", "palette:\n")
+                .replace("\nbase", "\n  base");
+            assert_eq!(
+                Theme::from_base16(&nested).unwrap(),
+                Theme::from_base16(BASE16).unwrap()
+            );
+        }
+
+        #[test]
+        fn test_from_base24() {
+            let source = format!(
+                "{BASE16}base10: \"101010\"\nbase11: \"111111\"\nbase12: ff1212\nbase13: \"131313\"\nbase14: \"141414\"\nbase15: \"151515\"\nbase16: \"161616\"\nbase17: \"171717\"\n"
+            );
+            let theme = Theme::from_base16(&source).unwrap();
+            assert_eq!(theme.palette[7], Color::new(0x66, 0x66, 0x66));
+            assert_eq!(theme.palette[8], Color::new(0x22, 0x22, 0x22));
+            assert_eq!(theme.palette[9], Color::new(0xff, 0x12, 0x12));
+            assert_eq!(theme.palette[12], Color::new(0x16, 0x16, 0x16));
+        }
+
+        #[test]
+        fn test_from_base16_errors() {
+            let err = Theme::from_base16(&BASE16.replace("base08: \"880000\"\n", "")).unwrap_err();
+            assert_eq!(err.to_string(), "invalid theme: base16: missing `base08`");
+
+            let err = Theme::from_base16(&BASE16.replace("\"880000\"", "\"zz\"")).unwrap_err();
+            assert_eq!(err.to_string(), "invalid theme: base16: invalid color `zz`");
+
+            assert!(Theme::from_base16("- base00").is_err());
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_theme_resolve() {
+        let theme = Theme::nord();
+        let default = theme.foreground;
+        assert_eq!(theme.resolve(CellColor::Default, default), default);
+        assert_eq!(
+            theme.resolve(CellColor::Indexed(196), default),
+            Color::new(255, 0, 0)
+        );
+        let rgb = Color::new(1, 2, 3);
+        assert_eq!(theme.resolve(CellColor::Rgb(rgb), default), rgb);
+    }
+
+    #[test]
+    fn test_theme_resolve_uses_palette() {
+        let theme = Theme::gruvbox();
+        let default = theme.foreground;
+        assert_eq!(
+            theme.resolve(CellColor::Indexed(1), default),
+            Color::new(204, 36, 29)
+        );
+        assert_eq!(
+            theme.resolve(CellColor::Indexed(9), default),
+            Color::new(251, 73, 52)
+        );
+        // Beyond the 16 ANSI colors the standard palette applies
+        assert_eq!(
+            theme.resolve(CellColor::Indexed(16), default),
+            Color::indexed(16)
+        );
+    }
+
+    #[test]
+    fn test_theme_presets_have_palettes() {
+        assert_eq!(Theme::dark().palette, Theme::DEFAULT_PALETTE);
+        for theme in [
+            Theme::nord(),
+            Theme::dracula(),
+            Theme::monokai(),
+            Theme::solarized_dark(),
+            Theme::solarized_light(),
+            Theme::light(),
+            Theme::github_dark(),
+            Theme::tokyo_night(),
+            Theme::catppuccin(),
+            Theme::one_dark(),
+            Theme::gruvbox(),
+        ] {
+            assert_ne!(theme.palette, Theme::DEFAULT_PALETTE);
+        }
+        assert_eq!(
+            Theme::solarized_dark().palette,
+            Theme::solarized_light().palette
+        );
+    }
+
+    #[test]
+    fn test_theme_is_dark() {
+        assert!(Theme::dark().is_dark());
+        assert!(Theme::nord().is_dark());
+        assert!(!Theme::light().is_dark());
+        assert!(!Theme::solarized_light().is_dark());
+    }
+
+    #[test]
+    fn test_theme_mode() {
+        let fixed = ThemeMode::from(Theme::nord());
+        assert_eq!(fixed.theme(false), Theme::nord());
+        assert_eq!(fixed.theme(true), Theme::nord());
+
+        let auto = ThemeMode::Auto {
+            light: Theme::light(),
+            dark: Theme::dracula(),
+        };
+        assert_eq!(auto.theme(false), Theme::light());
+        assert_eq!(auto.theme(true), Theme::dracula());
+        assert_eq!(ThemeMode::default(), ThemeMode::Fixed(Theme::dark()));
+    }
+
     #[test]
     fn test_theme_default_is_dark() {
         assert_eq!(Theme::default(), Theme::dark());
@@ -1,11 +1,14 @@
 //! Dioxus terminal widget component
 
+use dioxus::html::geometry::WheelDelta;
 use dioxus::prelude::*;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, PoisonError};
 
-use crate::pty::Pty;
-use crate::term::{Cell, Color, Grid};
-use crate::theme::Theme;
+use crate::emulator::{DEFAULT_SCROLLBACK_LINES, Emulator};
+use crate::pty::{DEFAULT_TERM, ExitStatus, Pty, PtyBuilder, PtySignal};
+use crate::term::{Cell, CellColor, Color, Grid, Style};
+use crate::theme::{Theme, ThemeMode};
+use crate::{Error, Result};
 
 /// Default monospace font stack
 pub const DEFAULT_FONT_FAMILY: &str =
@@ -26,6 +29,22 @@ pub struct TerminalProps {
     #[props(default)]
     pub shell: String,
 
+    /// Working directory (default: the home directory)
+    #[props(default)]
+    pub cwd: Option<String>,
+
+    /// Extra environment variables
+    #[props(default)]
+    pub env: Vec<(String, String)>,
+
+    /// Start from an empty environment instead of inheriting it
+    #[props(default)]
+    pub env_clear: bool,
+
+    /// Value of `TERM` (default: `xterm-256color`)
+    #[props(default = DEFAULT_TERM.to_string())]
+    pub term: String,
+
     /// Number of rows (default: 24)
     #[props(default = 24)]
     pub rows: u16,
@@ -34,6 +53,14 @@ pub struct TerminalProps {
     #[props(default = 120)]
     pub cols: u16,
 
+    /// Fit rows and columns to the container, overriding `rows` and `cols`
+    #[props(default)]
+    pub fit: bool,
+
+    /// Lines of scrollback history to keep (default: 10000)
+    #[props(default = DEFAULT_SCROLLBACK_LINES)]
+    pub scrollback_lines: usize,
+
     /// Font size in pixels (default: 13)
     #[props(default = 13)]
     pub font_size: u16,
@@ -42,9 +69,13 @@ pub struct TerminalProps {
     #[props(default = DEFAULT_FONT_FAMILY.to_string())]
     pub font_family: String,
 
-    /// Color theme (default: `Theme::dark()`)
-    #[props(default)]
-    pub theme: Theme,
+    /// Color theme, fixed or following the desktop (default: `Theme::dark()`)
+    ///
+    /// Accepts a [`Theme`] or a [`ThemeMode`]. Cells keep default and
+    /// indexed colors unresolved, so a new theme re-skins the screen and
+    /// scrollback without restarting the program.
+    #[props(default, into)]
+    pub theme: ThemeMode,
 
     /// Background color (overrides theme if set)
     #[props(default)]
@@ -57,58 +88,217 @@ pub struct TerminalProps {
     /// CSS class for the container
     #[props(default)]
     pub class: String,
+
+    /// Called once with a handle for controlling the terminal
+    #[props(default)]
+    pub on_ready: Option<EventHandler<TerminalHandle>>,
+
+    /// Called once when the program exits
+    #[props(default)]
+    pub on_exit: Option<EventHandler<ExitStatus>>,
+
+    /// Called if the program cannot be started
+    #[props(default)]
+    pub on_error: Option<EventHandler<Error>>,
+
+    /// Called with the new window title when the program changes it (OSC 0/2)
+    #[props(default)]
+    pub on_title_change: Option<EventHandler<String>>,
 }
 
 fn default_shell() -> String {
     std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string())
 }
 
-/// Escape sequence parsing state
-#[derive(Default)]
-enum EscapeState {
-    #[default]
-    Normal,
-    Escape,    // Just saw ESC
-    Csi,       // In CSI sequence (ESC [)
-    Osc,       // In OSC sequence (ESC ]) - consume until BEL or ST
-    OscEscape, // In OSC, just saw ESC (looking for \)
-    Dcs,       // In DCS sequence (ESC P) - consume until ST
-    DcsEscape, // In DCS, just saw ESC (looking for \)
-}
+/// Line height relative to the font size
+const LINE_HEIGHT: f64 = 1.2;
+
+/// Character advance relative to the font size for typical monospace fonts
+const CHAR_WIDTH: f64 = 0.6;
+
+/// Number of characters rendered to measure the cell width
+const MEASURE_COLUMNS: u8 = 10;
+
+/// Reports whether the webview prefers a dark color scheme, now and on change
+const COLOR_SCHEME_SCRIPT: &str = r"
+const query = window.matchMedia('(prefers-color-scheme: dark)');
+dioxus.send(query.matches);
+query.addEventListener('change', (event) => dioxus.send(event.matches));
+";
 
 /// Terminal state shared between render and coroutine
-#[allow(clippy::struct_excessive_bools)]
 struct TermState {
     pty: Option<Pty>,
-    cursor_row: usize,
-    cursor_col: usize,
-    // Current text attributes
-    fg: Color,
-    bg: Color,
-    bold: bool,
-    dim: bool,
-    italic: bool,
-    underline: bool,
-    // Escape sequence parsing
-    escape_state: EscapeState,
-    escape_buf: Vec<u8>,
+    emulator: Emulator,
+    spawn_error: Option<Error>, // Taken once it has been reported
+}
+
+impl TermState {
+    /// Send the emulator's replies, such as query answers, to the program
+    fn send_responses(&mut self) {
+        let responses = self.emulator.take_responses();
+        if let (Some(pty), false) = (&self.pty, responses.is_empty()) {
+            let _ = pty.write(&responses);
+        }
+    }
+}
+
+/// Signals the rendered screen is read from
+#[derive(Clone, Copy)]
+struct View {
+    grid: Signal<Grid>,
+    cursor: Signal<Option<(usize, usize)>>, // (row, col), None if hidden
+    title: Signal<String>,
+    icon_name: Signal<String>,
+}
+
+impl View {
+    /// Publish the emulator's current view for rendering
+    fn refresh(mut self, emulator: &Emulator) {
+        self.grid.set(emulator.grid());
+        self.cursor.set(emulator.viewport_cursor());
+    }
+
+    /// Publish the emulator's titles, returning the window title if it changed
+    fn refresh_titles(mut self, emulator: &Emulator) -> Option<String> {
+        if *self.icon_name.peek() != emulator.icon_name() {
+            self.icon_name.set(emulator.icon_name().to_owned());
+        }
+        if *self.title.peek() == emulator.title() {
+            return None;
+        }
+        let title = emulator.title().to_owned();
+        self.title.set(title.clone());
+        Some(title)
+    }
+}
+
+/// Handle for controlling a running [`Terminal`]
+///
+/// Delivered through the `on_ready` prop.
+#[derive(Clone)]
+pub struct TerminalHandle {
+    state: Arc<Mutex<TermState>>,
+    view: View,
+}
+
+impl std::fmt::Debug for TerminalHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TerminalHandle").finish_non_exhaustive()
+    }
+}
+
+impl TerminalHandle {
+    /// Scroll the view by `lines`, positive values moving back into history
+    pub fn scroll_lines(&self, lines: i32) {
+        self.update(|emulator| emulator.scroll_lines(lines));
+    }
+
+    /// Scroll the view back by one screen
+    pub fn scroll_page_up(&self) {
+        self.update(Emulator::scroll_page_up);
+    }
+
+    /// Scroll the view forward by one screen
+    pub fn scroll_page_down(&self) {
+        self.update(Emulator::scroll_page_down);
+    }
+
+    /// Scroll the view to the oldest line in history
+    pub fn scroll_to_top(&self) {
+        self.update(Emulator::scroll_to_top);
+    }
+
+    /// Scroll the view back to the live screen
+    pub fn scroll_to_bottom(&self) {
+        self.update(Emulator::scroll_to_bottom);
+    }
+
+    /// Get how many lines the view is scrolled back into history
+    #[must_use]
+    pub fn display_offset(&self) -> usize {
+        self.state.lock().map_or(0, |s| s.emulator.display_offset())
+    }
+
+    /// Window title set by the program (OSC 0/2), empty if none
+    #[must_use]
+    pub fn title(&self) -> ReadSignal<String> {
+        self.view.title.into()
+    }
+
+    /// Icon name set by the program (OSC 0/1), empty if none
+    #[must_use]
+    pub fn icon_name(&self) -> ReadSignal<String> {
+        self.view.icon_name.into()
+    }
+
+    /// Send a signal to the running program
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the signal cannot be delivered.
+    pub fn signal(&self, signal: PtySignal) -> Result<()> {
+        let mut s = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        s.pty.as_mut().map_or(Ok(()), |pty| pty.signal(signal))
+    }
+
+    /// Kill the running program immediately
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the signal cannot be delivered.
+    pub fn kill(&self) -> Result<()> {
+        self.signal(PtySignal::Kill)
+    }
+
+    /// Run `f` against the emulator and re-render the result
+    fn update(&self, f: impl FnOnce(&mut Emulator)) {
+        if let Ok(mut s) = self.state.lock() {
+            f(&mut s.emulator);
+            self.view.refresh(&s.emulator);
+        }
+    }
 }
 
 /// Terminal emulator widget for Dioxus
 #[component]
 pub fn Terminal(props: TerminalProps) -> Element {
-    let rows = props.rows as usize;
-    let cols = props.cols as usize;
+    // Measured cell and container sizes in pixels, as (width, height)
+    let mut cell_size = use_signal(|| None::<(f64, f64)>);
+    let mut container_size = use_signal(|| None::<(f64, f64)>);
+
+    // In fit mode the grid follows the container once both are measured
+    let (term_rows, term_cols) = match (props.fit, cell_size(), container_size()) {
+        (true, Some(cell), Some(container)) => fit_size(container, cell),
+        _ => (props.rows, props.cols),
+    };
+    let rows = usize::from(term_rows);
+    let cols = usize::from(term_cols);
+
+    // Track the desktop's light/dark preference for `ThemeMode::Auto`
+    let mut prefers_dark = use_signal(|| true);
+    use_future(move || async move {
+        let mut query = document::eval(COLOR_SCHEME_SCRIPT);
+        while let Ok(dark) = query.recv::<bool>().await {
+            prefers_dark.set(dark);
+        }
+    });
+    let theme = props.theme.theme(prefers_dark());
 
     // Resolve colors: explicit props override theme
-    let bg_color = props.background.unwrap_or(props.theme.background);
-    let fg_color = props.foreground.unwrap_or(props.theme.foreground);
+    let bg_color = props.background.unwrap_or(theme.background);
+    let fg_color = props.foreground.unwrap_or(theme.foreground);
 
-    let mut grid = use_signal(|| Grid::new(rows, cols));
-    let mut cursor_pos = use_signal(|| (0usize, 0usize)); // (row, col)
+    let view = View {
+        grid: use_signal(|| Grid::new(rows, cols)),
+        cursor: use_signal(|| Some((0, 0))),
+        title: use_signal(String::new),
+        icon_name: use_signal(String::new),
+    };
+    let mut wheel_pixels = use_signal(|| 0.0f64);
 
     // Shared state for PTY and cursor
-    let state = use_hook(|| {
+    let (state, error_message) = use_hook(|| {
         // If shell prop is set, use sh -c to run it
         let (command, args): (String, Vec<String>) = if props.shell.is_empty() {
             (props.command.clone(), props.args.clone())
@@ -119,49 +309,120 @@ pub fn Terminal(props: TerminalProps) -> Element {
             )
         };
 
-        let args_refs: Vec<&str> = args.iter().map(String::as_str).collect();
-        let pty = Pty::spawn(&command, &args_refs, props.rows, props.cols).ok();
+        let mut builder = PtyBuilder::new(command)
+            .args(args)
+            .size(term_rows, term_cols)
+            .term(props.term.clone());
+        if let Some(ref dir) = props.cwd {
+            builder = builder.cwd(dir);
+        }
+        if props.env_clear {
+            builder = builder.env_clear();
+        }
+        for (key, value) in &props.env {
+            builder = builder.env(key, value);
+        }
+        let (pty, spawn_error) = match builder.spawn() {
+            Ok(pty) => (Some(pty), None),
+            Err(err) => (None, Some(err)),
+        };
+        let error_message = spawn_error.as_ref().map(ToString::to_string);
 
-        Arc::new(Mutex::new(TermState {
+        let state = Arc::new(Mutex::new(TermState {
             pty,
-            cursor_row: 0,
-            cursor_col: 0,
-            fg: Color::default_fg(),
-            bg: Color::default_bg(),
-            bold: false,
-            dim: false,
-            italic: false,
-            underline: false,
-            escape_state: EscapeState::Normal,
-            escape_buf: Vec::new(),
-        }))
+            emulator: Emulator::with_scrollback(rows, cols, props.scrollback_lines),
+            spawn_error,
+        }));
+        (state, error_message)
     });
 
+    // Propagate size changes to the emulator and the running program
+    let state_for_size = state.clone();
+    use_effect(use_reactive!(|(term_rows, term_cols)| {
+        let Ok(mut guard) = state_for_size.lock() else {
+            return;
+        };
+        let s = &mut *guard;
+        let size = (usize::from(term_rows), usize::from(term_cols));
+        if (s.emulator.rows(), s.emulator.cols()) != size {
+            s.emulator.resize(size.0, size.1);
+            if let Some(ref mut pty) = s.pty {
+                let _ = pty.resize(term_rows, term_cols);
+            }
+            view.refresh(&s.emulator);
+        }
+    }));
+
+    // Answer color queries and DSR 996/997 from the active colors
+    let state_for_theme = state.clone();
+    let active_theme = Theme {
+        background: bg_color,
+        foreground: fg_color,
+        ..theme
+    };
+    use_effect(use_reactive!(|active_theme| {
+        if let Ok(mut s) = state_for_theme.lock() {
+            s.emulator.set_theme(active_theme);
+            s.send_responses();
+        }
+    }));
+
+    // Apply scrollback limit changes without restarting the program
+    let state_for_scrollback = state.clone();
+    let scrollback_lines = props.scrollback_lines;
+    use_effect(use_reactive!(|scrollback_lines| {
+        if let Ok(mut s) = state_for_scrollback.lock() {
+            if s.emulator.scrollback_lines() != scrollback_lines {
+                s.emulator.set_scrollback_lines(scrollback_lines);
+                view.refresh(&s.emulator);
+            }
+        }
+    }));
+
     // Coroutine to read PTY output
     let state_clone = state.clone();
+    let on_exit = props.on_exit;
+    let on_title_change = props.on_title_change;
     use_coroutine(move |_rx: UnboundedReceiver<()>| {
         let state = state_clone.clone();
         async move {
+            let mut exit_reported = false;
             loop {
-                // Try to read from PTY
-                let data = {
-                    let mut s = state.lock().unwrap();
+                // Drain pending PTY output into the emulator
+                let (exited, title) = {
+                    let mut guard = state.lock().unwrap();
+                    let s = &mut *guard;
+                    let mut changed = s.emulator.flush_expired_sync();
+                    let mut exited = None;
                     if let Some(ref mut pty) = s.pty {
-                        pty.try_read()
-                    } else {
-                        None
+                        // Check before draining so the final output is shown first
+                        if !exit_reported {
+                            exited = pty.try_wait().ok().flatten();
+                        }
+                        while let Some(bytes) = pty.try_read() {
+                            s.emulator.advance(&bytes);
+                            changed = true;
+                        }
+                    }
+                    // Answer queries found in the output
+                    s.send_responses();
+                    let mut title = None;
+                    if changed {
+                        view.refresh(&s.emulator);
+                        title = view.refresh_titles(&s.emulator);
                     }
+                    (exited, title)
                 };
 
-                if let Some(bytes) = data {
-                    // Process output bytes
-                    let mut s = state.lock().unwrap();
-                    for byte in bytes {
-                        process_byte(&mut s, &mut grid, byte, rows, cols);
+                if let (Some(on_title_change), Some(title)) = (on_title_change, title) {
+                    on_title_change.call(title);
+                }
+
+                if let Some(status) = exited {
+                    exit_reported = true;
+                    if let Some(on_exit) = on_exit {
+                        on_exit.call(status);
                     }
-                    // Update cursor position signal
-                    cursor_pos.set((s.cursor_row, s.cursor_col));
-                    drop(s);
                 }
 
                 // Small delay to avoid busy loop
@@ -170,26 +431,122 @@ pub fn Terminal(props: TerminalProps) -> Element {
         }
     });
 
+    let handle = TerminalHandle {
+        state: state.clone(),
+        view,
+    };
+    let on_ready = props.on_ready;
+    use_effect(move || {
+        if let Some(on_ready) = on_ready {
+            on_ready.call(handle.clone());
+        }
+    });
+
+    // Report a failed spawn to the host app
+    let state_for_error = state.clone();
+    let on_error = props.on_error;
+    use_effect(move || {
+        let spawn_error = state_for_error
+            .lock()
+            .ok()
+            .and_then(|mut s| s.spawn_error.take());
+        if let (Some(on_error), Some(err)) = (on_error, spawn_error) {
+            on_error.call(err);
+        }
+    });
+
     // Handle keyboard input
     let state_for_key = state.clone();
     let onkeydown = move |evt: KeyboardEvent| {
+        let Ok(mut s) = state_for_key.lock() else {
+            return;
+        };
+
+        // Shift+PageUp/PageDown page through scrollback on the primary screen
+        if evt.modifiers().shift() && !s.emulator.is_alt_screen() {
+            match evt.key() {
+                Key::PageUp => s.emulator.scroll_page_up(),
+                Key::PageDown => s.emulator.scroll_page_down(),
+                _ => {}
+            }
+            if matches!(evt.key(), Key::PageUp | Key::PageDown) {
+                view.refresh(&s.emulator);
+                return;
+            }
+        }
+
         let key_str = key_to_string(&evt);
         if !key_str.is_empty() {
-            if let Ok(s) = state_for_key.lock() {
-                if let Some(ref pty) = s.pty {
-                    let _ = pty.write(key_str.as_bytes());
+            if let Some(ref pty) = s.pty {
+                let _ = pty.write(key_str.as_bytes());
+            }
+            // Typing snaps the view back to the live screen
+            if s.emulator.display_offset() > 0 {
+                s.emulator.scroll_to_bottom();
+                view.refresh(&s.emulator);
+            }
+        }
+    };
+
+    // Handle mouse wheel: scroll history, or forward to the program
+    let state_for_wheel = state.clone();
+    let font_size = f64::from(props.font_size);
+    let page_rows = term_rows;
+    let (cell_width, line_height) =
+        cell_size().unwrap_or((font_size * CHAR_WIDTH, font_size * LINE_HEIGHT));
+    let onwheel = move |evt: WheelEvent| {
+        let mut carry = wheel_pixels();
+        // Wheel down (positive delta) moves towards the live screen
+        let lines = -wheel_lines(evt.delta(), line_height, page_rows, &mut carry);
+        wheel_pixels.set(carry);
+        if lines == 0 {
+            return;
+        }
+
+        let point = evt.element_coordinates();
+        let row = cell_index(point.y, line_height, rows);
+        let col = cell_index(point.x, cell_width, cols);
+
+        if let Ok(mut s) = state_for_wheel.lock() {
+            match s.emulator.wheel(lines, row, col) {
+                Some(bytes) => {
+                    if let Some(ref pty) = s.pty {
+                        let _ = pty.write(&bytes);
+                    }
                 }
+                None => view.refresh(&s.emulator),
+            }
+        }
+    };
+
+    // Track the container size for fit mode
+    let fit = props.fit;
+    let onresize = move |evt: ResizeEvent| {
+        if let (true, Ok(size)) = (fit, evt.get_content_box_size()) {
+            container_size.set(Some((size.width, size.height)));
+        }
+    };
+
+    // Measure the rendered cell size of the current font
+    let onmeasure = move |evt: MountedEvent| async move {
+        if let Ok(rect) = evt.get_client_rect().await {
+            let width = rect.width() / f64::from(MEASURE_COLUMNS);
+            if width > 0.0 && rect.height() > 0.0 {
+                cell_size.set(Some((width, rect.height())));
             }
         }
     };
 
-    let container_style = format!(
-        "background-color: {}; color: {}; font-family: {}; font-size: {}px; line-height: 1.2;",
+    let mut container_style = format!(
+        "position: relative; background-color: {}; color: {}; font-family: {}; font-size: {}px; line-height: {LINE_HEIGHT};",
         bg_color.to_css(),
         fg_color.to_css(),
         props.font_family,
         props.font_size
     );
+    if props.fit {
+        container_style.push_str(" width: 100%; height: 100%;");
+    }
 
     let container_class = format!(
         "terminal-container overflow-hidden select-none {}",
@@ -202,33 +559,53 @@ pub fn Terminal(props: TerminalProps) -> Element {
             style: "{container_style}",
             tabindex: "0",
             onkeydown: onkeydown,
+            onresize: onresize,
+
+            // Hidden sample text; remounted when the font changes
+            span {
+                key: "{props.font_family}-{props.font_size}",
+                style: "position: absolute; top: 0; left: 0; visibility: hidden; white-space: pre; pointer-events: none;",
+                onmounted: onmeasure,
+                {"0".repeat(MEASURE_COLUMNS.into())}
+            }
 
-            // Render grid
-            div { class: "terminal-grid whitespace-pre font-mono",
-                for (row_idx, row) in grid.read().iter_rows().enumerate() {
-                    div { class: "terminal-row", key: "{row_idx}",
-                        for (col_idx, cell) in row.iter().enumerate() {
-                            {
-                                let (cursor_row, cursor_col) = *cursor_pos.read();
-                                let is_cursor = row_idx == cursor_row && col_idx == cursor_col;
-                                // Use theme background for cells with default black bg
-                                let cell_bg = if cell.bg == Color::default_bg() {
-                                    bg_color
-                                } else {
-                                    cell.bg
-                                };
-                                // Invert colors for cursor (block cursor style)
-                                let (fg, bg) = if is_cursor {
-                                    (cell_bg.to_css(), fg_color.to_css())
-                                } else {
-                                    (cell.fg.to_css(), cell_bg.to_css())
-                                };
-                                rsx! {
-                                    span {
-                                        key: "{col_idx}",
-                                        class: "{cell.style.to_css_classes()}",
-                                        style: "color: {fg}; background-color: {bg};",
-                                        "{cell.c}"
+            // Spawn failures replace the grid with the error message
+            if let Some(message) = error_message {
+                div { class: "terminal-error whitespace-pre-wrap", role: "alert", style: "padding: 0.5em 1ch;",
+                    "{message}"
+                }
+            } else {
+                // Render grid; rows ignore the pointer so wheel positions are grid-relative
+                div { class: "terminal-grid whitespace-pre font-mono", onwheel: onwheel,
+                    for (row_idx, row) in view.grid.read().iter_rows().enumerate() {
+                        div { class: "terminal-row", key: "{row_idx}", style: "pointer-events: none;",
+                            // Wide characters span the spacer cell that follows them
+                            for (col_idx, cell) in row.iter().enumerate().filter(|(_, c)| !c.is_wide_spacer()) {
+                                {
+                                    let is_cursor = *view.cursor.read() == Some((row_idx, col_idx));
+                                    let (fg, bg) = cell_colors(cell, &theme, fg_color, bg_color, is_cursor);
+                                    let (fg, bg) = (fg.to_css(), bg.to_css());
+                                    let width = if cell.width > 1 {
+                                        " display: inline-block; width: 2ch;"
+                                    } else {
+                                        ""
+                                    };
+                                    let decoration = cell_decoration(cell, &theme, fg_color);
+                                    let lines = cell.style.to_line_css();
+                                    let classes = cell_classes(cell, &theme);
+                                    let text = cell.text();
+                                    rsx! {
+                                        span {
+                                            key: "{col_idx}",
+                                            class: "{classes}",
+                                            style: "color: {fg}; background-color: {bg};{width}{decoration}",
+                                            // Overline and strikethrough keep their own shape and color
+                                            if lines.is_empty() {
+                                                "{text}"
+                                            } else {
+                                                span { style: "{lines}", "{text}" }
+                                            }
+                                        }
                                     }
                                 }
                             }
@@ -240,274 +617,93 @@ pub fn Terminal(props: TerminalProps) -> Element {
     }
 }
 
-/// Process a single byte of terminal output
-fn process_byte(
-    state: &mut TermState,
-    grid: &mut Signal<Grid>,
-    byte: u8,
-    rows: usize,
-    cols: usize,
-) {
-    match state.escape_state {
-        EscapeState::Normal => match byte {
-            // Escape - start escape sequence
-            0x1b => {
-                state.escape_state = EscapeState::Escape;
-                state.escape_buf.clear();
-            }
-            // Newline
-            b'\n' => {
-                state.cursor_row += 1;
-                if state.cursor_row >= rows {
-                    scroll_up(grid, rows, cols);
-                    state.cursor_row = rows - 1;
-                }
-            }
-            // Carriage return
-            b'\r' => {
-                state.cursor_col = 0;
-            }
-            // Backspace
-            0x08 => {
-                if state.cursor_col > 0 {
-                    state.cursor_col -= 1;
-                }
-            }
-            // Tab
-            b'\t' => {
-                let next_tab = (state.cursor_col / 8 + 1) * 8;
-                state.cursor_col = next_tab.min(cols - 1);
-            }
-            // Printable characters
-            0x20..=0x7e | 0x80..=0xff => {
-                let c = byte as char;
-                let cell = Cell {
-                    c,
-                    fg: state.fg,
-                    bg: state.bg,
-                    style: crate::term::Style {
-                        bold: state.bold,
-                        dim: state.dim,
-                        italic: state.italic,
-                        underline: state.underline,
-                        strikethrough: false,
-                        inverse: false,
-                    },
-                };
-                grid.write().set(state.cursor_row, state.cursor_col, cell);
-                state.cursor_col += 1;
-                if state.cursor_col >= cols {
-                    state.cursor_col = 0;
-                    state.cursor_row += 1;
-                    if state.cursor_row >= rows {
-                        scroll_up(grid, rows, cols);
-                        state.cursor_row = rows - 1;
-                    }
-                }
-            }
-            // Other control characters - ignore
-            _ => {}
-        },
-        EscapeState::Escape => {
-            match byte {
-                b'[' => state.escape_state = EscapeState::Csi,
-                b']' => state.escape_state = EscapeState::Osc,
-                b'P' => state.escape_state = EscapeState::Dcs,
-                // Single-character sequences - just ignore and return to normal
-                // ESC 7 (save cursor), ESC 8 (restore cursor), ESC c (reset), etc.
-                _ => state.escape_state = EscapeState::Normal,
-            }
-        }
-        EscapeState::Osc => {
-            // OSC sequences end with BEL (0x07) or ST (ESC \)
-            match byte {
-                0x07 => state.escape_state = EscapeState::Normal,
-                0x1b => state.escape_state = EscapeState::OscEscape,
-                _ => {} // Consume all other bytes
-            }
-        }
-        EscapeState::OscEscape => {
-            if byte == b'\\' {
-                state.escape_state = EscapeState::Normal;
-            } else {
-                state.escape_state = EscapeState::Osc;
-            }
-        }
-        EscapeState::Dcs => {
-            // DCS sequences end with ST (ESC \)
-            if byte == 0x1b {
-                state.escape_state = EscapeState::DcsEscape;
-            }
-            // Consume all other bytes
-        }
-        EscapeState::DcsEscape => {
-            if byte == b'\\' {
-                state.escape_state = EscapeState::Normal;
-            } else {
-                state.escape_state = EscapeState::Dcs;
-            }
-        }
-        EscapeState::Csi => {
-            if byte.is_ascii_alphabetic() {
-                // End of CSI sequence
-                if byte == b'm' {
-                    // SGR - Select Graphic Rendition
-                    process_sgr(state);
-                }
-                // Other CSI sequences (cursor movement, etc.) - ignore for now
-                state.escape_state = EscapeState::Normal;
-                state.escape_buf.clear();
-            } else {
-                // Buffer the parameter bytes
-                state.escape_buf.push(byte);
-            }
-        }
-    }
-}
+/// Resolve the (foreground, background) a cell is drawn with
+///
+/// `fg_color` and `bg_color` are used for cells with the default colors,
+/// except where the theme sets a dim or bold color.
+fn cell_colors(
+    cell: &Cell,
+    theme: &Theme,
+    fg_color: Color,
+    bg_color: Color,
+    is_cursor: bool,
+) -> (Color, Color) {
+    let default_fg = theme
+        .dim
+        .filter(|_| cell.style.dim)
+        .or(theme.bold.filter(|_| cell.style.bold))
+        .unwrap_or(fg_color);
+    let fg = theme.resolve(cell.fg, default_fg);
+    let bg = theme.resolve(cell.bg, bg_color);
 
-/// Process SGR (Select Graphic Rendition) escape sequence
-fn process_sgr(state: &mut TermState) {
-    let params_str = String::from_utf8_lossy(&state.escape_buf);
-    let params: Vec<u8> = if params_str.is_empty() {
-        vec![0] // Default to reset
+    let (fg, bg) = if cell.style.inverse {
+        (bg, fg)
     } else {
-        params_str
-            .split(';')
-            .filter_map(|s| s.parse().ok())
-            .collect()
+        (fg, bg)
     };
+    let fg = if cell.style.hidden { bg } else { fg };
+    // Invert colors for cursor (block cursor style)
+    if is_cursor { (bg, fg) } else { (fg, bg) }
+}
 
-    let mut i = 0;
-    while i < params.len() {
-        match params[i] {
-            0 => {
-                // Reset all attributes
-                state.fg = Color::default_fg();
-                state.bg = Color::default_bg();
-                state.bold = false;
-                state.dim = false;
-                state.italic = false;
-                state.underline = false;
-            }
-            1 => state.bold = true,
-            2 => state.dim = true,
-            3 => state.italic = true,
-            4 => state.underline = true,
-            22 => {
-                state.bold = false;
-                state.dim = false;
-            }
-            23 => state.italic = false,
-            24 => state.underline = false,
-            // Standard foreground colors (30-37)
-            30 => state.fg = Color::new(0, 0, 0),       // Black
-            31 => state.fg = Color::new(205, 49, 49),   // Red
-            32 => state.fg = Color::new(13, 188, 121),  // Green
-            33 => state.fg = Color::new(229, 229, 16),  // Yellow
-            34 => state.fg = Color::new(36, 114, 200),  // Blue
-            35 => state.fg = Color::new(188, 63, 188),  // Magenta
-            36 => state.fg = Color::new(17, 168, 205),  // Cyan
-            37 => state.fg = Color::new(229, 229, 229), // White
-            39 => state.fg = Color::default_fg(),       // Default fg
-            // Standard background colors (40-47)
-            40 => state.bg = Color::new(0, 0, 0),       // Black
-            41 => state.bg = Color::new(205, 49, 49),   // Red
-            42 => state.bg = Color::new(13, 188, 121),  // Green
-            43 => state.bg = Color::new(229, 229, 16),  // Yellow
-            44 => state.bg = Color::new(36, 114, 200),  // Blue
-            45 => state.bg = Color::new(188, 63, 188),  // Magenta
-            46 => state.bg = Color::new(17, 168, 205),  // Cyan
-            47 => state.bg = Color::new(229, 229, 229), // White
-            49 => state.bg = Color::default_bg(),       // Default bg
-            // Bright foreground colors (90-97)
-            90 => state.fg = Color::new(102, 102, 102), // Bright black
-            91 => state.fg = Color::new(241, 76, 76),   // Bright red
-            92 => state.fg = Color::new(35, 209, 139),  // Bright green
-            93 => state.fg = Color::new(245, 245, 67),  // Bright yellow
-            94 => state.fg = Color::new(59, 142, 234),  // Bright blue
-            95 => state.fg = Color::new(214, 112, 214), // Bright magenta
-            96 => state.fg = Color::new(41, 184, 219),  // Bright cyan
-            97 => state.fg = Color::new(255, 255, 255), // Bright white
-            // Bright background colors (100-107)
-            100 => state.bg = Color::new(102, 102, 102),
-            101 => state.bg = Color::new(241, 76, 76),
-            102 => state.bg = Color::new(35, 209, 139),
-            103 => state.bg = Color::new(245, 245, 67),
-            104 => state.bg = Color::new(59, 142, 234),
-            105 => state.bg = Color::new(214, 112, 214),
-            106 => state.bg = Color::new(41, 184, 219),
-            107 => state.bg = Color::new(255, 255, 255),
-            // 256-color mode (38;5;N or 48;5;N)
-            38 => {
-                if i + 2 < params.len() && params[i + 1] == 5 {
-                    state.fg = color_from_256(params[i + 2]);
-                    i += 2;
-                }
-            }
-            48 => {
-                if i + 2 < params.len() && params[i + 1] == 5 {
-                    state.bg = color_from_256(params[i + 2]);
-                    i += 2;
-                }
-            }
-            _ => {}
-        }
-        i += 1;
-    }
+/// CSS classes for a cell's style
+///
+/// Text decorations are drawn with inline CSS so they keep their own shape
+/// and color. Dim text drawn in the theme's dim color isn't faded again.
+fn cell_classes(cell: &Cell, theme: &Theme) -> String {
+    let dim = cell.style.dim && (theme.dim.is_none() || cell.fg != CellColor::Default);
+    let style = Style {
+        underline: false,
+        strikethrough: false,
+        overline: false,
+        dim,
+        ..cell.style
+    };
+    style.to_css_classes()
 }
 
-/// Convert 256-color palette index to RGB
-fn color_from_256(n: u8) -> Color {
-    match n {
-        // Standard colors (0-15)
-        0 => Color::new(0, 0, 0),
-        1 => Color::new(205, 49, 49),
-        2 => Color::new(13, 188, 121),
-        3 => Color::new(229, 229, 16),
-        4 => Color::new(36, 114, 200),
-        5 => Color::new(188, 63, 188),
-        6 => Color::new(17, 168, 205),
-        7 => Color::new(229, 229, 229),
-        8 => Color::new(102, 102, 102),
-        9 => Color::new(241, 76, 76),
-        10 => Color::new(35, 209, 139),
-        11 => Color::new(245, 245, 67),
-        12 => Color::new(59, 142, 234),
-        13 => Color::new(214, 112, 214),
-        14 => Color::new(41, 184, 219),
-        15 => Color::new(255, 255, 255),
-        // 216-color cube (16-231)
-        16..=231 => {
-            let n = n - 16;
-            let r = (n / 36) % 6;
-            let g = (n / 6) % 6;
-            let b = n % 6;
-            let to_255 = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
-            Color::new(to_255(r), to_255(g), to_255(b))
-        }
-        // Grayscale (232-255)
-        232..=255 => {
-            let gray = 8 + (n - 232) * 10;
-            Color::new(gray, gray, gray)
+/// Inline CSS for a cell's underline, with a leading space
+fn cell_decoration(cell: &Cell, theme: &Theme, fg_color: Color) -> String {
+    let css = cell.style.to_underline_css();
+    match cell.underline_color {
+        _ if css.is_empty() => css,
+        Some(color) => {
+            let color = theme.resolve(color, fg_color);
+            format!(" {css} text-decoration-color: {};", color.to_css())
         }
+        None => format!(" {css}"),
     }
 }
 
-/// Scroll the grid up by one line
-fn scroll_up(grid: &mut Signal<Grid>, rows: usize, cols: usize) {
-    let mut g = grid.write();
-    // Move all rows up by one
-    for row in 1..rows {
-        for col in 0..cols {
-            if let Some(cell) = g.get(row, col).cloned() {
-                g.set(row - 1, col, cell);
-            }
+/// Convert a wheel delta into whole lines, carrying partial pixel movement
+#[allow(clippy::cast_possible_truncation)]
+fn wheel_lines(delta: WheelDelta, line_height: f64, page_rows: u16, carry: &mut f64) -> i32 {
+    let lines = match delta {
+        WheelDelta::Pixels(delta) => {
+            // Accumulate small trackpad deltas into whole lines
+            let total = *carry + delta.y;
+            let lines = (total / line_height).trunc();
+            *carry = total - lines * line_height;
+            lines
         }
-    }
-    // Clear the last row
-    for col in 0..cols {
-        g.set(rows - 1, col, Cell::default());
-    }
+        WheelDelta::Lines(delta) => delta.y.round(),
+        WheelDelta::Pages(delta) => (delta.y * f64::from(page_rows)).round(),
+    };
+    lines as i32
+}
+
+/// Compute the (rows, cols) that fit a container, given (width, height) sizes
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn fit_size(container: (f64, f64), cell: (f64, f64)) -> (u16, u16) {
+    let rows = (container.1 / cell.1).floor().max(1.0) as u16;
+    let cols = (container.0 / cell.0).floor().max(1.0) as u16;
+    (rows, cols)
+}
+
+/// Convert a pixel offset into a cell index, clamped to `0..count`
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn cell_index(pixels: f64, cell_size: f64, count: usize) -> usize {
+    ((pixels / cell_size).max(0.0) as usize).min(count.saturating_sub(1))
 }
 
 /// Convert keyboard event to terminal input string
@@ -526,6 +722,8 @@ fn key_to_string(evt: &KeyboardEvent) -> String {
         Key::Home => "\x1b[H".to_string(),
         Key::End => "\x1b[F".to_string(),
         Key::Delete => "\x1b[3~".to_string(),
+        Key::PageUp => "\x1b[5~".to_string(),
+        Key::PageDown => "\x1b[6~".to_string(),
         Key::Character(c) => {
             // Handle Ctrl+key combinations
             if evt.modifiers().ctrl() && c.len() == 1 {
@@ -558,23 +756,262 @@ mod tests {
             command: "bash".to_string(),
             args: vec![],
             shell: String::new(),
+            cwd: None,
+            env: vec![],
+            env_clear: false,
+            term: DEFAULT_TERM.to_string(),
             rows: 24,
             cols: 120,
+            fit: false,
+            scrollback_lines: DEFAULT_SCROLLBACK_LINES,
             font_size: 13,
             font_family: DEFAULT_FONT_FAMILY.to_string(),
-            theme: Theme::default(),
+            theme: ThemeMode::default(),
             background: None,
             foreground: None,
             class: String::new(),
+            on_ready: None,
+            on_exit: None,
+            on_error: None,
+            on_title_change: None,
         };
 
         assert_eq!(props.rows, 24);
         assert_eq!(props.cols, 120);
+        assert!(!props.fit);
+        assert_eq!(props.scrollback_lines, 10_000);
         assert_eq!(props.font_size, 13);
-        assert_eq!(props.theme, Theme::dark());
+        assert_eq!(props.term, "xterm-256color");
+        assert_eq!(props.theme, ThemeMode::Fixed(Theme::dark()));
+    }
+
+    #[test]
+    fn test_terminal_props_spawn_options() {
+        let props = TerminalProps::builder()
+            .cwd("/tmp")
+            .env(vec![("EDITOR".to_string(), "vim".to_string())])
+            .build()
+            .inner;
+
+        assert_eq!(props.cwd.as_deref(), Some("/tmp"));
+        assert_eq!(props.env.len(), 1);
+        assert!(!props.env_clear);
+        assert_eq!(props.term, DEFAULT_TERM);
+    }
+
+    #[test]
+    fn test_terminal_renders_spawn_error() {
+        let html = dioxus_ssr::render_element(rsx! {
+            Terminal { command: "no-such-command-here" }
+        });
+        assert!(html.contains("terminal-error"));
+        assert!(html.contains("command not found: no-such-command-here"));
+        assert!(!html.contains("terminal-grid"));
+    }
+
+    #[test]
+    fn test_terminal_renders_grid() {
+        let html = dioxus_ssr::render_element(rsx! {
+            Terminal { command: "true", rows: 2, cols: 4 }
+        });
+        assert!(html.contains("terminal-grid"));
+        assert!(!html.contains("terminal-error"));
+    }
+
+    /// Create a runtime for the timers of the output coroutine
+    #[cfg(unix)]
+    fn test_runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+    }
+
+    /// Build `dom` and render it as work arrives until `done` holds
+    #[cfg(unix)]
+    fn render_until(dom: &mut VirtualDom, mut done: impl FnMut(&VirtualDom) -> bool) {
+        test_runtime().block_on(async {
+            dom.rebuild_in_place();
+            let wait = async {
+                while !done(dom) {
+                    dom.wait_for_work().await;
+                    dom.render_immediate(&mut dioxus::dioxus_core::NoOpMutations);
+                }
+            };
+            tokio::time::timeout(std::time::Duration::from_secs(5), wait)
+                .await
+                .expect("render condition never held");
+        });
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_terminal_theme_switch() {
+        fn app() -> Element {
+            let theme = use_context::<Signal<Theme>>();
+            rsx! {
+                Terminal { command: "sleep", args: vec!["5".to_string()], rows: 1, cols: 2, theme: theme() }
+            }
+        }
+
+        // The output coroutine sleeps on tokio's timer
+        let runtime = test_runtime();
+        let _guard = runtime.enter();
+
+        let mut dom = VirtualDom::new(app);
+        let theme = dom.in_scope(ScopeId::ROOT, || Signal::new(Theme::dark()));
+        dom.provide_root_context(theme);
+        dom.rebuild_in_place();
+        let html = dioxus_ssr::render(&dom);
+        // The second cell isn't under the cursor, so it has the theme colors
+        assert!(html.contains("color: rgb(204, 204, 204); background-color: rgb(0, 0, 0);"));
+
+        dom.in_runtime(|| theme.clone().set(Theme::light()));
+        dom.mark_dirty(ScopeId::APP);
+        dom.render_immediate(&mut dioxus::dioxus_core::NoOpMutations);
+        let html = dioxus_ssr::render(&dom);
+        assert!(html.contains("color: rgb(30, 30, 30); background-color: rgb(255, 255, 255);"));
+        assert!(!html.contains("terminal-error"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_terminal_reports_title_change() {
+        type Titles = Arc<Mutex<Vec<String>>>;
+        fn app() -> Element {
+            let titles = use_context::<Titles>();
+            rsx! {
+                Terminal {
+                    shell: "printf '\\033]2;hello\\007'; sleep 5",
+                    on_title_change: move |title| titles.lock().unwrap().push(title),
+                }
+            }
+        }
+
+        let titles = Titles::default();
+        let mut dom = VirtualDom::new(app).with_root_context(titles.clone());
+        render_until(&mut dom, |_| !titles.lock().unwrap().is_empty());
+        assert_eq!(*titles.lock().unwrap(), ["hello"]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_terminal_renders_combining_characters() {
+        fn app() -> Element {
+            rsx! {
+                Terminal { shell: "printf 'Gru\\314\\210sse'; sleep 5", rows: 1, cols: 10 }
+            }
+        }
+
+        let mut dom = VirtualDom::new(app);
+        render_until(&mut dom, |dom| {
+            dioxus_ssr::render(dom).contains(">u\u{0308}<")
+        });
+    }
+
+    #[test]
+    fn test_cell_colors() {
+        let theme = Theme::dark();
+        let fg = Color::new(200, 200, 200);
+        let bg = Color::new(10, 10, 10);
+        let red = Color::new(255, 0, 0);
+
+        let plain = Cell::new('a');
+        assert_eq!(cell_colors(&plain, &theme, fg, bg, false), (fg, bg));
+        assert_eq!(cell_colors(&plain, &theme, fg, bg, true), (bg, fg));
+
+        let mut cell = Cell::with_colors('a', CellColor::Indexed(196), CellColor::Default);
+        cell.style.inverse = true;
+        assert_eq!(cell_colors(&cell, &theme, fg, bg, false), (bg, red));
+
+        cell.style.hidden = true;
+        assert_eq!(cell_colors(&cell, &theme, fg, bg, false), (red, red));
+    }
+
+    #[test]
+    fn test_cell_colors_bold_and_dim() {
+        let mut theme = Theme::dark();
+        let fg = theme.foreground;
+        let bg = theme.background;
+        let mut cell = Cell::new('a');
+        cell.style.bold = true;
+        assert_eq!(cell_colors(&cell, &theme, fg, bg, false).0, fg);
+
+        let bold = Color::new(255, 255, 255);
+        let dim = Color::new(100, 100, 100);
+        theme.bold = Some(bold);
+        theme.dim = Some(dim);
+        assert_eq!(cell_colors(&cell, &theme, fg, bg, false).0, bold);
+
+        cell.style.dim = true;
+        assert_eq!(cell_colors(&cell, &theme, fg, bg, false).0, dim);
+        assert_eq!(cell_classes(&cell, &theme), "font-bold");
+
+        // Explicit colors are kept and faded
+        cell.fg = CellColor::Indexed(1);
+        assert_eq!(
+            cell_colors(&cell, &theme, fg, bg, false).0,
+            theme.palette[1]
+        );
+        assert_eq!(cell_classes(&cell, &theme), "font-bold opacity-50");
+    }
+
+    #[test]
+    fn test_cell_decoration() {
+        let theme = Theme::dark();
+        let fg = theme.foreground;
+        let mut cell = Cell::new('a');
+        cell.underline_color = Some(CellColor::Indexed(196));
+        assert_eq!(cell_decoration(&cell, &theme, fg), "");
+
+        cell.style.overline = true;
+        assert_eq!(cell_decoration(&cell, &theme, fg), "");
+
+        cell.style.underline = true;
+        assert_eq!(
+            cell_decoration(&cell, &theme, fg),
+            " text-decoration-line: underline; text-decoration-color: rgb(255, 0, 0);"
+        );
+    }
+
+    #[test]
+    fn test_wheel_lines() {
+        let mut carry = 0.0;
+        assert_eq!(
+            wheel_lines(WheelDelta::lines(0.0, 3.0, 0.0), 15.0, 24, &mut carry),
+            3
+        );
+        assert_eq!(
+            wheel_lines(WheelDelta::pages(0.0, -1.0, 0.0), 15.0, 24, &mut carry),
+            -24
+        );
+        assert_eq!(
+            wheel_lines(WheelDelta::pixels(0.0, 10.0, 0.0), 15.0, 24, &mut carry),
+            0
+        );
+        assert_eq!(
+            wheel_lines(WheelDelta::pixels(0.0, 10.0, 0.0), 15.0, 24, &mut carry),
+            1
+        );
+        assert!((carry - 5.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_fit_size() {
+        assert_eq!(fit_size((800.0, 600.0), (8.0, 15.0)), (40, 100));
+        assert_eq!(fit_size((805.5, 614.9), (8.0, 15.0)), (40, 100));
+        assert_eq!(fit_size((0.0, 0.0), (8.0, 15.0)), (1, 1));
+    }
+
+    #[test]
+    fn test_cell_index() {
+        assert_eq!(cell_index(31.0, 15.0, 24), 2);
+        assert_eq!(cell_index(-4.0, 15.0, 24), 0);
+        assert_eq!(cell_index(10_000.0, 15.0, 24), 23);
     }
 
     #[test]
+    #[allow(clippy::unnecessary_literal_unwrap)]
     fn test_theme_override() {
         // Background/foreground props should override theme
         let theme = Theme::zinc();